@@ -0,0 +1,177 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+
+use swc_common::SyntaxContext;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Visit, VisitWith};
+
+/// Names that refer to the global object in browsers, workers and Node
+pub const GLOBAL_OBJECTS: [&str; 4] = ["globalThis", "window", "self", "global"];
+
+/// Tracks local bindings that alias built-in globals, so that `const P = Promise; P.any()`,
+/// `const { fromEntries } = Object` and `Object['values']` are recognized like direct references.
+/// Bindings are told apart by the syntax context the resolver assigned to them.
+#[derive(Debug, Default)]
+pub struct Aliases {
+    // context of identifiers the resolver could not bind, i.e. globals
+    unresolved: SyntaxContext,
+    // `const P = Promise`
+    globals: HashMap<Id, String>,
+    // `const { any } = Promise` or `const any = Promise.any`
+    members: HashMap<Id, (String, String)>,
+    // `const s = new Set()`
+    instances: HashMap<Id, String>,
+    // bindings that are assigned after their declaration, their value is unknown
+    reassigned: HashSet<Id>,
+}
+
+impl Aliases {
+    /// Collects the aliases declared anywhere in the program
+    pub fn collect(program: &Program, unresolved: SyntaxContext) -> Self {
+        let mut aliases = Aliases {
+            unresolved,
+            ..Default::default()
+        };
+        program.visit_with(&mut aliases);
+        for id in std::mem::take(&mut aliases.reassigned) {
+            aliases.globals.remove(&id);
+            aliases.members.remove(&id);
+            aliases.instances.remove(&id);
+        }
+        aliases
+    }
+
+    /// Name of the global an expression refers to, through aliases and the global object
+    pub fn global_name(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Ident(i) if i.ctxt == self.unresolved => Some(i.sym.to_string()),
+            Expr::Ident(i) => self.globals.get(&i.to_id()).cloned(),
+            Expr::Member(m) => {
+                let object = self.global_name(&m.obj)?;
+                if GLOBAL_OBJECTS.contains(&object.deref()) {
+                    prop_name(&m.prop)
+                } else {
+                    None
+                }
+            }
+            Expr::Paren(p) => self.global_name(&p.expr),
+            _ => None,
+        }
+    }
+
+    /// Global and property name of a static member like `Object.values`, `Object['values']` or a
+    /// binding destructured from a global
+    pub fn static_member(&self, expr: &Expr) -> Option<(String, String)> {
+        match expr {
+            Expr::Member(m) => Some((self.global_name(&m.obj)?, prop_name(&m.prop)?)),
+            Expr::Ident(i) => self.members.get(&i.to_id()).cloned(),
+            Expr::Paren(p) => self.static_member(&p.expr),
+            // `(0, Object.values)(o)` as emitted by bundlers
+            Expr::Seq(s) => self.static_member(s.exprs.last()?),
+            _ => None,
+        }
+    }
+
+    /// Name of the global constructor an expression was created with, for `new Set()`,
+    /// `Symbol()`, array and string literals and bindings initialised with them
+    pub fn instance_of(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::New(n) => self.global_name(&n.callee),
+            Expr::Array(_) => Some("Array".to_string()),
+            Expr::Lit(Lit::Str(_)) | Expr::Tpl(_) => Some("String".to_string()),
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                ..
+            }) => self.global_name(callee).filter(|c| c == "Symbol"),
+            Expr::Ident(i) => self.instances.get(&i.to_id()).cloned(),
+            Expr::Paren(p) => self.instance_of(&p.expr),
+            _ => None,
+        }
+    }
+}
+
+impl Visit for Aliases {
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        // `P = x`, `P ||= x`, `[P] = xs` and `({ any } = x)` all replace the alias
+        match &n.left {
+            AssignTarget::Simple(SimpleAssignTarget::Ident(binding)) => {
+                self.reassigned.insert(binding.to_id());
+            }
+            AssignTarget::Pat(pat) => {
+                let mut bindings = Bindings::default();
+                pat.visit_with(&mut bindings);
+                self.reassigned.extend(bindings.0);
+            }
+            _ => {}
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+        if let Some(init) = &n.init {
+            match &n.name {
+                Pat::Ident(binding) => {
+                    if let Some(global) = self.global_name(init) {
+                        self.globals.insert(binding.to_id(), global);
+                    } else if let Some(member) = self.static_member(init) {
+                        self.members.insert(binding.to_id(), member);
+                    } else if let Some(constructor) = self.instance_of(init) {
+                        self.instances.insert(binding.to_id(), constructor);
+                    }
+                }
+                Pat::Object(pattern) => {
+                    if let Some(global) = self.global_name(init) {
+                        for prop in &pattern.props {
+                            match prop {
+                                ObjectPatProp::Assign(p) => {
+                                    self.members.insert(
+                                        p.key.to_id(),
+                                        (global.clone(), p.key.sym.to_string()),
+                                    );
+                                }
+                                ObjectPatProp::KeyValue(p) => {
+                                    let name = match &p.key {
+                                        PropName::Ident(i) => Some(i.sym.to_string()),
+                                        PropName::Str(s) => Some(s.value.to_string()),
+                                        _ => None,
+                                    };
+                                    if let (Some(name), Pat::Ident(binding)) =
+                                        (name, p.value.deref())
+                                    {
+                                        self.members
+                                            .insert(binding.to_id(), (global.clone(), name));
+                                    }
+                                }
+                                ObjectPatProp::Rest(_) => {}
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        n.visit_children_with(self)
+    }
+}
+
+/// Identifiers bound by a destructuring pattern
+#[derive(Default)]
+struct Bindings(Vec<Id>);
+
+impl Visit for Bindings {
+    fn visit_binding_ident(&mut self, n: &BindingIdent) {
+        self.0.push(n.to_id());
+    }
+}
+
+/// Name of a member, for identifiers and string literals in brackets
+pub fn prop_name(prop: &MemberProp) -> Option<String> {
+    match prop {
+        MemberProp::Ident(i) => Some(i.sym.to_string()),
+        MemberProp::Computed(c) => match c.expr.deref() {
+            Expr::Lit(Lit::Str(s)) => Some(s.value.to_string()),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
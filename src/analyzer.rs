@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use strum::IntoEnumIterator;
+
+use swc_common::FileName;
+
+use crate::browserslist::Browser;
+use crate::compat::CompatTable;
+use crate::diagnostic::Diagnostic;
+use crate::es_features::FeatureKind;
+use crate::es_version::EsVersion;
+use crate::parse_goal::ParseGoal;
+use crate::report::{FeatureReport, Report};
+use crate::{analyse, read_js_file, Error, ParseOptions, Result};
+
+/// Analyses sources with a fixed configuration.
+/// The analyzer holds no parser state, so one instance can be shared across threads.
+#[derive(Debug, Clone, Default)]
+pub struct Analyzer {
+    target: Option<EsVersion>,
+    parse: ParseOptions,
+    tsconfig_target: Option<EsVersion>,
+    count_conditional: bool,
+    compat: Option<Arc<CompatTable>>,
+    browsers: Vec<Browser>,
+    browser_compat: Option<Arc<CompatTable>>,
+    threads: Option<usize>,
+}
+
+/// Result of analysing one file of a batch
+#[derive(Debug)]
+pub struct FileResult {
+    pub path: PathBuf,
+    /// The report, or the error that prevented reading the file
+    pub report: Result<Report>,
+    /// Time spent reading and analysing the file
+    pub duration: Duration,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks features newer than `target` in the reports
+    pub fn with_target(mut self, target: Option<EsVersion>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Grammar sources are parsed with, defaults to [`ParseGoal::Auto`]
+    pub fn with_goal(mut self, goal: ParseGoal) -> Self {
+        self.parse.goal = goal;
+        self
+    }
+
+    /// Parses all sources as JSX. Files ending with `.jsx` or `.tsx` are always parsed as JSX.
+    pub fn with_jsx(mut self, jsx: bool) -> Self {
+        self.parse.jsx = jsx;
+        self
+    }
+
+    /// Parses all sources as TypeScript.
+    /// Files ending with `.ts`, `.tsx`, `.mts` or `.cts` are always parsed as TypeScript.
+    pub fn with_typescript(mut self, typescript: bool) -> Self {
+        self.parse.typescript = typescript;
+        self
+    }
+
+    /// Warns about TypeScript sources that need a newer version than the `target` of a
+    /// `tsconfig.json`, see [`crate::read_tsconfig_target`]
+    pub fn with_tsconfig_target(mut self, target: Option<EsVersion>) -> Self {
+        self.tsconfig_target = target;
+        self
+    }
+
+    /// Detects Stage 2.7/3 proposals such as decorators or `Temporal`, which are not part of any
+    /// edition and fail every target. Disabled by default, decorators are then a parse error.
+    pub fn with_proposals(mut self, proposals: bool) -> Self {
+        self.parse.proposals = proposals;
+        self
+    }
+
+    /// Counts built-ins behind a feature test like `typeof Promise.any === 'function'` as required.
+    /// By default they are reported as conditional and do not raise the minimum version.
+    pub fn with_count_conditional(mut self, count_conditional: bool) -> Self {
+        self.count_conditional = count_conditional;
+        self
+    }
+
+    /// Reports the minimum engine versions according to the table, see [`CompatTable::embedded`]
+    pub fn with_compat_table(mut self, compat: Option<Arc<CompatTable>>) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Checks every feature against the browsers, see [`crate::BrowserData::resolve`].
+    /// Support is looked up in the compatibility table, the embedded one by default.
+    pub fn with_browsers(mut self, browsers: Vec<Browser>) -> Self {
+        self.browser_compat =
+            (!browsers.is_empty()).then(|| Arc::new(CompatTable::embedded().clone()));
+        self.browsers = browsers;
+        self
+    }
+
+    /// Number of threads used by [`Analyzer::analyse_files`], defaults to one per CPU
+    pub fn with_threads(mut self, threads: Option<usize>) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Analyses Javascript source, parse errors and other failures end up in the report's
+    /// diagnostics
+    pub fn analyse_source(&self, js: impl AsRef<str>, file_name: impl Into<String>) -> Report {
+        let file = file_name.into();
+        let analysis = match analyse(js, FileName::Custom(file.clone()), &self.parse) {
+            Ok(analysis) => analysis,
+            Err(e) => {
+                let diagnostics = match e {
+                    Error::ParserError { diagnostics } if !diagnostics.is_empty() => diagnostics,
+                    e => vec![Diagnostic::from_error(&file, &e)],
+                };
+                return Report {
+                    file,
+                    min_version: None,
+                    min_version_after_polyfills: None,
+                    min_version_by_kind: BTreeMap::new(),
+                    engines: BTreeMap::new(),
+                    target: self.target,
+                    browsers: self.browsers.clone(),
+                    goal: None,
+                    requires_module: false,
+                    polyfills: Vec::new(),
+                    features: Vec::new(),
+                    diagnostics,
+                    warnings: Vec::new(),
+                };
+            }
+        };
+
+        let mut required = analysis.locations();
+        let mut conditional = analysis.conditional_locations();
+        // a feature that is also used unguarded is required, with all its occurrences
+        conditional.retain(|feature, locations| match required.get_mut(feature) {
+            Some(required) => {
+                required.append(locations);
+                required.sort();
+                false
+            }
+            None => true,
+        });
+        if self.count_conditional {
+            required.extend(conditional.drain());
+        }
+
+        let polyfilled = analysis.finder.get_polyfilled();
+        let browser_compat = self.compat.as_ref().or(self.browser_compat.as_ref());
+        let mut features: Vec<FeatureReport> = required
+            .into_iter()
+            .map(|(feature, locations)| (feature, locations, false))
+            .chain(
+                conditional
+                    .into_iter()
+                    .map(|(feature, locations)| (feature, locations, true)),
+            )
+            .map(|(feature, locations, conditional)| {
+                let unsupported_browsers: Vec<Browser> = match browser_compat {
+                    Some(compat) if !conditional => self
+                        .browsers
+                        .iter()
+                        .filter(|b| b.supports(feature, compat) == Some(false))
+                        .cloned()
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                FeatureReport {
+                    feature,
+                    kind: feature.kind(),
+                    version: feature.edition(),
+                    above_target: !conditional
+                        && (self
+                            .target
+                            .is_some_and(|t| feature.edition().is_none_or(|v| v > t))
+                            || !unsupported_browsers.is_empty()),
+                    conditional,
+                    polyfilled: polyfilled.contains(&feature),
+                    unsupported_browsers,
+                    locations,
+                }
+            })
+            .collect();
+        features.sort_by_key(|f| (f.version.is_none(), f.version, f.feature));
+
+        let min_version = features
+            .iter()
+            .filter(|f| !f.conditional)
+            .filter_map(|f| f.version)
+            .max()
+            .unwrap_or(EsVersion::ES3);
+        let min_version_after_polyfills = features
+            .iter()
+            .filter(|f| !f.conditional && !f.polyfilled)
+            .filter_map(|f| f.version)
+            .max()
+            .unwrap_or(EsVersion::ES3);
+        let min_version_by_kind = FeatureKind::iter()
+            .map(|kind| {
+                let min_version = features
+                    .iter()
+                    .filter(|f| f.kind == kind && !f.conditional)
+                    .filter_map(|f| f.version)
+                    .max()
+                    .unwrap_or(EsVersion::ES3);
+                (kind, min_version)
+            })
+            .collect();
+        let engines = self
+            .compat
+            .as_ref()
+            .map(|compat| {
+                compat.min_versions(
+                    features
+                        .iter()
+                        .filter(|f| !f.conditional)
+                        .map(|f| f.feature),
+                )
+            })
+            .unwrap_or_default();
+        let mut warnings = Vec::new();
+        if let Some(tsconfig_target) = self.tsconfig_target {
+            if analysis.typescript && min_version > tsconfig_target {
+                warnings.push(format!(
+                    "requires {} but tsconfig.json targets {}",
+                    min_version, tsconfig_target
+                ));
+            }
+        }
+
+        Report {
+            file,
+            min_version: Some(min_version),
+            min_version_after_polyfills: Some(min_version_after_polyfills),
+            min_version_by_kind,
+            engines,
+            target: self.target,
+            browsers: self.browsers.clone(),
+            goal: Some(analysis.goal),
+            requires_module: analysis.finder.requires_module(),
+            polyfills: analysis.finder.get_polyfills().sources.clone(),
+            features,
+            diagnostics: analysis.diagnostics,
+            warnings,
+        }
+    }
+
+    /// Reads and analyses a single file
+    pub fn analyse_file(&self, path: impl AsRef<Path>) -> Result<Report> {
+        let path = path.as_ref();
+        let js = read_js_file(path)?;
+        Ok(self.analyse_source(js, path.display().to_string()))
+    }
+
+    /// Analyses the files in parallel, results are returned in the order of `paths`
+    pub fn analyse_files(&self, paths: &[PathBuf]) -> Result<Vec<FileResult>> {
+        let analyse = || {
+            paths
+                .par_iter()
+                .map(|path| {
+                    let start = Instant::now();
+                    let report = self.analyse_file(path);
+                    FileResult {
+                        path: path.clone(),
+                        report,
+                        duration: start.elapsed(),
+                    }
+                })
+                .collect()
+        };
+
+        match self.threads {
+            None => Ok(analyse()),
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map(|pool| pool.install(analyse))
+                .map_err(|e| Error::ThreadPool(e.to_string())),
+        }
+    }
+}
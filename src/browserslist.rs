@@ -0,0 +1,708 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+use crate::compat::{CompatTable, Engine, EngineVersion};
+use crate::es_features::EsFeature;
+use crate::es_version::EsVersion;
+use crate::{read_text_file, Error, Result};
+
+/// Versions and global usage of browsers, a snapshot of caniuse data. Node releases with their
+/// end of life and the Firefox ESR versions are maintained by hand, as in browserslist itself.
+/// Once the last end of life has passed, `maintained node versions` selects nothing.
+const EMBEDDED: &str = include_str!("../data/browsers.json");
+
+/// Query of `defaults`
+const DEFAULTS: &str = "> 0.5%, last 2 versions, Firefox ESR, not dead";
+
+/// Query of `dead`, browsers without updates for 24 months
+const DEAD: &str = "baidu >= 0, ie <= 11, ie_mob <= 11, bb <= 10, op_mob <= 12.1, samsung 4";
+
+/// Alternative names of browsers in queries
+const ALIASES: [(&str, &str); 14] = [
+    ("fx", "firefox"),
+    ("ff", "firefox"),
+    ("ios", "ios_saf"),
+    ("explorer", "ie"),
+    ("blackberry", "bb"),
+    ("explorermobile", "ie_mob"),
+    ("operamini", "op_mini"),
+    ("operamobile", "op_mob"),
+    ("chromeandroid", "and_chr"),
+    ("firefoxandroid", "and_ff"),
+    ("ucandroid", "and_uc"),
+    ("qqandroid", "and_qq"),
+    ("nodejs", "node"),
+    ("samsunginternet", "samsung"),
+];
+
+/// Chrome versions of Samsung Internet releases
+const SAMSUNG_CHROME: [(&str, &str); 25] = [
+    ("4", "44"),
+    ("5.0-5.4", "51"),
+    ("6.2-6.4", "56"),
+    ("7.2-7.4", "59"),
+    ("8.2", "63"),
+    ("9.2", "67"),
+    ("10.1", "71"),
+    ("11.1-11.2", "75"),
+    ("12.0", "79"),
+    ("13.0", "83"),
+    ("14.0", "87"),
+    ("15.0", "90"),
+    ("16.0", "92"),
+    ("17.0", "96"),
+    ("18.0", "99"),
+    ("19.0", "102"),
+    ("20", "106"),
+    ("21", "110"),
+    ("22", "111"),
+    ("23", "115"),
+    ("24", "117"),
+    ("25", "121"),
+    ("26", "122"),
+    ("27", "125"),
+    ("28", "130"),
+];
+
+/// A browser version a query resolved to, like `chrome 120` or `ios_saf 16.6-16.7`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Browser {
+    /// Name as in caniuse, like `and_chr`
+    pub name: String,
+    /// Version as in caniuse, ranges like `16.6-16.7` for iOS
+    pub version: String,
+}
+
+impl Browser {
+    /// Engine the browser runs Javascript with and its version, `None` for browsers without
+    /// compatibility data like Opera Mini
+    pub fn engine(&self) -> Option<(Engine, EngineVersion)> {
+        let lower = self.version.split('-').next().unwrap_or_default();
+        let major = || lower.split('.').next()?.parse::<u32>().ok();
+        let (engine, version) = match self.name.as_str() {
+            "chrome" | "and_chr" => (Engine::Chrome, lower.to_string()),
+            "android" if major()? >= 37 => (Engine::Chrome, lower.to_string()),
+            // Opera follows Chromium 13 versions behind since Opera 15
+            "opera" if major()? >= 15 => (Engine::Chrome, (major()? + 13).to_string()),
+            "samsung" => {
+                let (_, chrome) = SAMSUNG_CHROME.iter().find(|(v, _)| *v == self.version)?;
+                (Engine::Chrome, chrome.to_string())
+            }
+            "edge" => (Engine::Edge, lower.to_string()),
+            "firefox" | "and_ff" => (Engine::Firefox, lower.to_string()),
+            "kaios" if self.version == "2.5" => (Engine::Firefox, "48".to_string()),
+            "kaios" => (Engine::Firefox, "84".to_string()),
+            "safari" => (Engine::Safari, lower.to_string()),
+            "ios_saf" => (Engine::IosSafari, lower.to_string()),
+            "node" => (Engine::Node, lower.to_string()),
+            _ => return None,
+        };
+        Some((engine, version.parse().ok()?))
+    }
+
+    /// `true` if there is data for the browser, [`Browser::supports`] is `None` otherwise
+    pub fn has_compat_data(&self) -> bool {
+        let Some(version) = version_key(&self.version) else {
+            return false;
+        };
+        match self.name.as_str() {
+            "ie" => true,
+            "android" if version.first().copied().unwrap_or_default() < 37 => true,
+            _ => self.engine().is_some(),
+        }
+    }
+
+    /// Whether the browser supports a feature, `None` if there is no data for the browser.
+    /// Internet Explorer from version 9 and the Android browser before Chrome took over are
+    /// taken to support ES5 and nothing newer.
+    pub fn supports(&self, feature: EsFeature, compat: &CompatTable) -> Option<bool> {
+        let major = version_key(&self.version)?
+            .first()
+            .copied()
+            .unwrap_or_default();
+        let es5 = match self.name.as_str() {
+            "ie" if feature == EsFeature::StrictMode => Some(major >= 10),
+            "ie" => Some(major >= 9),
+            "android" if major < 37 => Some(major >= 4),
+            _ => None,
+        };
+        if let Some(es5) = es5 {
+            return Some(es5 && feature.version() <= EsVersion::ES5);
+        }
+        let (engine, version) = self.engine()?;
+        Some(
+            compat
+                .first_version(feature, engine)
+                .is_some_and(|first| *first <= version),
+        )
+    }
+}
+
+impl Display for Browser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+impl Serialize for Browser {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl PartialOrd for Browser {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Browser {
+    /// By name, newest versions first like browserslist
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.cmp(&other.name).then_with(|| {
+            version_key(&other.version)
+                .cmp(&version_key(&self.version))
+                .then_with(|| other.version.cmp(&self.version))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Agent {
+    /// Released versions, oldest first, with their global usage in percent
+    released: Vec<(String, f64)>,
+    unreleased: Vec<String>,
+}
+
+/// Browser versions and usage that browserslist queries are evaluated against
+#[derive(Debug, Clone)]
+pub struct BrowserData {
+    agents: BTreeMap<String, Agent>,
+    firefox_esr: Vec<String>,
+    /// Latest release of each Node major, oldest first, with its end of life
+    node: Vec<(String, String)>,
+}
+
+impl BrowserData {
+    /// The snapshot compiled into the binary, parsed on first use
+    pub fn embedded() -> &'static BrowserData {
+        static DATA: OnceLock<BrowserData> = OnceLock::new();
+        DATA.get_or_init(BrowserData::parse_embedded)
+    }
+
+    fn parse_embedded() -> BrowserData {
+        let json: Value = serde_json::from_str(EMBEDDED).expect("embedded browser data is valid");
+        let strings = |value: &Value| -> Vec<String> {
+            value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        };
+        let agents = json["agents"]
+            .as_object()
+            .expect("embedded browser data has agents")
+            .iter()
+            .map(|(name, agent)| {
+                let released = agent["released"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| Some((v[0].as_str()?.to_string(), v[1].as_f64()?)))
+                    .collect();
+                let unreleased = strings(&agent["unreleased"]);
+                (
+                    name.clone(),
+                    Agent {
+                        released,
+                        unreleased,
+                    },
+                )
+            })
+            .collect();
+        let node = json["node"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| Some((v[0].as_str()?.to_string(), v[1].as_str()?.to_string())))
+            .collect();
+        BrowserData {
+            agents,
+            firefox_esr: strings(&json["firefox_esr"]),
+            node,
+        }
+    }
+
+    /// Updates the browser versions and usage from the `data.json` of caniuse
+    /// (`fulldata-json/data-2.0.json` in its repository). Node and Firefox ESR keep their embedded
+    /// versions.
+    pub fn from_caniuse_file(path: impl AsRef<Path>) -> Result<BrowserData> {
+        let path = path.as_ref();
+        let config_error = |message: String| Error::Config {
+            path: path.display().to_string(),
+            message,
+        };
+        let json: Value = serde_json::from_str(&read_text_file(path)?)
+            .map_err(|e| config_error(e.to_string()))?;
+        let agents = json
+            .get("agents")
+            .and_then(Value::as_object)
+            .ok_or_else(|| config_error("agents is missing".into()))?;
+
+        let mut data = BrowserData::embedded().clone();
+        for (name, agent) in agents {
+            let Some(versions) = agent.get("version_list").and_then(Value::as_array) else {
+                continue;
+            };
+            let mut parsed = Agent {
+                released: Vec::new(),
+                unreleased: Vec::new(),
+            };
+            for v in versions {
+                let Some(version) = v.get("version").and_then(Value::as_str) else {
+                    continue;
+                };
+                if v.get("release_date").is_none_or(Value::is_null) {
+                    parsed.unreleased.push(version.to_string());
+                } else {
+                    let usage = v.get("global_usage").and_then(Value::as_f64).unwrap_or(0.0);
+                    parsed.released.push((version.to_string(), usage));
+                }
+            }
+            data.agents.insert(name.clone(), parsed);
+        }
+        Ok(data)
+    }
+
+    /// Evaluates a browserslist query like `defaults, not IE 11` or `last 2 versions and > 1%`.
+    /// Browsers are sorted by name, newest versions first.
+    pub fn resolve(&self, query: &str) -> Result<Vec<Browser>> {
+        self.resolve_on(query, today())
+    }
+
+    /// Like [`BrowserData::resolve`] as of a date like `2026-04-30`. `maintained node versions`
+    /// depends on the date, the Node end of life dates of the embedded snapshot go stale over time.
+    pub fn resolve_at(&self, query: &str, date: &str) -> Result<Vec<Browser>> {
+        let today = days_from_date(date).ok_or_else(|| Error::Query {
+            query: query.to_string(),
+            message: format!("invalid date {}", date),
+        })?;
+        self.resolve_on(query, today)
+    }
+
+    fn resolve_on(&self, query: &str, today: i64) -> Result<Vec<Browser>> {
+        self.resolve_queries(query, today)
+            .map_err(|message| Error::Query {
+                query: query.to_string(),
+                message,
+            })
+    }
+
+    fn resolve_queries(
+        &self,
+        query: &str,
+        today: i64,
+    ) -> std::result::Result<Vec<Browser>, String> {
+        let mut result: Vec<Browser> = Vec::new();
+        let lower = query.to_lowercase();
+        let mut first = true;
+        for alternative in lower.split([',', '\n']).flat_map(|q| q.split(" or ")) {
+            for (i, part) in alternative.split(" and ").enumerate() {
+                let part = part.split_whitespace().collect::<Vec<_>>().join(" ");
+                if part.is_empty() {
+                    continue;
+                }
+                let (negated, part) = match part.strip_prefix("not ") {
+                    Some(part) => (true, part),
+                    None => (false, part.as_str()),
+                };
+                // a query that starts with `not` removes browsers from the defaults
+                if first && negated {
+                    result = self.resolve_queries(DEFAULTS, today)?;
+                }
+                first = false;
+                let selected = self.resolve_single(part, today)?;
+                if negated {
+                    result.retain(|b| !selected.contains(b));
+                } else if i > 0 {
+                    result.retain(|b| selected.contains(b));
+                } else {
+                    result.extend(selected);
+                }
+            }
+        }
+        result.sort();
+        result.dedup();
+        Ok(result)
+    }
+
+    /// Browsers of a query without `,`, `or`, `and` and `not`, `today` in days since 1970-01-01
+    fn resolve_single(&self, query: &str, today: i64) -> std::result::Result<Vec<Browser>, String> {
+        // `> 0.5 %` is `>0.5%`
+        let share = query.replace(' ', "");
+        let words: Vec<&str> = if share.ends_with('%') {
+            vec![&share]
+        } else {
+            query.split(' ').collect()
+        };
+        match words.as_slice() {
+            ["defaults"] => self.resolve_queries(DEFAULTS, today),
+            ["dead"] => self.resolve_queries(DEAD, today),
+            [firefox, "esr"] if self.agent_name(firefox) == "firefox" => Ok(self
+                .firefox_esr
+                .iter()
+                .map(|v| browser("firefox", v))
+                .collect()),
+            ["maintained", "node", "versions"] => Ok(self
+                .node
+                .iter()
+                .filter(|(_, end)| days_from_date(end).is_some_and(|end| end > today))
+                .map(|(v, _)| browser("node", v))
+                .collect()),
+            ["unreleased", "versions"] => Ok(self
+                .agents
+                .iter()
+                .flat_map(|(name, a)| a.unreleased.iter().map(move |v| browser(name, v)))
+                .collect()),
+            ["unreleased", name, "versions"] => {
+                let name = self.agent_name(name);
+                let agent = self.agent(name)?;
+                Ok(agent.unreleased.iter().map(|v| browser(name, v)).collect())
+            }
+            ["last", n, rest @ ..] => {
+                let n: usize = n.parse().map_err(|_| format!("invalid count {}", n))?;
+                let (name, major) = match rest {
+                    ["versions"] => (None, false),
+                    ["major", "versions"] => (None, true),
+                    [name, "versions"] => (Some(*name), false),
+                    [name, "major", "versions"] => (Some(*name), true),
+                    _ => return Err(format!("unknown query {}", query)),
+                };
+                Ok(match name {
+                    Some(name) => {
+                        let name = self.agent_name(name);
+                        self.last_versions(name, &self.released(name)?, n, major)
+                    }
+                    None => self
+                        .agents
+                        .iter()
+                        .flat_map(|(name, agent)| {
+                            let versions: Vec<&str> =
+                                agent.released.iter().map(|(v, _)| v.as_str()).collect();
+                            self.last_versions(name, &versions, n, major)
+                        })
+                        .collect(),
+                })
+            }
+            [share] if share.ends_with('%') => {
+                let (op, share) = split_operator(share.trim_end_matches('%'));
+                let share: f64 = share
+                    .parse()
+                    .map_err(|_| format!("invalid usage {}%", share))?;
+                Ok(self
+                    .agents
+                    .iter()
+                    .flat_map(|(name, agent)| {
+                        agent
+                            .released
+                            .iter()
+                            .filter(|(_, usage)| compare(op, usage.partial_cmp(&share)))
+                            .map(move |(v, _)| browser(name, v))
+                    })
+                    .collect())
+            }
+            [name, op @ (">" | ">=" | "<" | "<="), version] => {
+                let name = self.agent_name(name);
+                let key =
+                    version_key(version).ok_or_else(|| format!("invalid version {}", version))?;
+                Ok(self
+                    .released(name)?
+                    .into_iter()
+                    .filter(|v| compare(op, version_key(v).map(|k| k.cmp(&key))))
+                    .map(|v| browser(name, v))
+                    .collect())
+            }
+            [name, version] => {
+                let name = self.agent_name(name);
+                let versions = self.released(name)?;
+                let mut versions: Vec<&str> = versions
+                    .into_iter()
+                    .chain(
+                        self.agents
+                            .get(name)
+                            .into_iter()
+                            .flat_map(|a| a.unreleased.iter().map(String::as_str)),
+                    )
+                    .collect();
+                // `node 18` is the latest release of Node 18
+                if name == "node" {
+                    versions.retain(|v| {
+                        *v == *version
+                            || v.strip_prefix(version).is_some_and(|r| r.starts_with('.'))
+                    });
+                    versions.truncate(1);
+                } else {
+                    versions.retain(|v| version_matches(v, version));
+                }
+                if versions.is_empty() {
+                    return match version.split_once('-') {
+                        Some((from, to)) => self.resolve_range(name, from, to),
+                        None => Err(format!("unknown version {} of {}", version, name)),
+                    };
+                }
+                Ok(versions.into_iter().map(|v| browser(name, v)).collect())
+            }
+            _ => Err(format!("unknown query {}", query)),
+        }
+    }
+
+    /// `safari 14-15.4`
+    fn resolve_range(
+        &self,
+        name: &str,
+        from: &str,
+        to: &str,
+    ) -> std::result::Result<Vec<Browser>, String> {
+        let (Some(from), Some(to)) = (version_key(from), version_key(to)) else {
+            return Err(format!("unknown version {}-{} of {}", from, to, name));
+        };
+        Ok(self
+            .released(name)?
+            .into_iter()
+            .filter(|v| version_key(v).is_some_and(|k| from <= k && k <= to))
+            .map(|v| browser(name, v))
+            .collect())
+    }
+
+    /// The last `n` versions, or all versions of the last `n` majors
+    fn last_versions(&self, name: &str, versions: &[&str], n: usize, major: bool) -> Vec<Browser> {
+        let selected: Vec<&str> = if major {
+            let mut majors: Vec<&str> = versions
+                .iter()
+                .map(|v| v.split(['.', '-']).next().unwrap_or(v))
+                .collect();
+            majors.dedup();
+            let majors = &majors[majors.len().saturating_sub(n)..];
+            versions
+                .iter()
+                .filter(|v| majors.contains(&v.split(['.', '-']).next().unwrap_or(v)))
+                .copied()
+                .collect()
+        } else {
+            versions[versions.len().saturating_sub(n)..].to_vec()
+        };
+        selected.into_iter().map(|v| browser(name, v)).collect()
+    }
+
+    /// Canonical name of a browser in a query
+    fn agent_name<'a>(&self, name: &'a str) -> &'a str {
+        ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map_or(name, |(_, name)| name)
+    }
+
+    fn agent(&self, name: &str) -> std::result::Result<&Agent, String> {
+        self.agents
+            .get(name)
+            .ok_or_else(|| format!("unknown browser {}", name))
+    }
+
+    /// Released versions of a browser or Node, oldest first
+    fn released(&self, name: &str) -> std::result::Result<Vec<&str>, String> {
+        if name == "node" {
+            return Ok(self.node.iter().map(|(v, _)| v.as_str()).collect());
+        }
+        Ok(self
+            .agent(name)?
+            .released
+            .iter()
+            .map(|(v, _)| v.as_str())
+            .collect())
+    }
+}
+
+/// Reads the browserslist query for `env` from a `.browserslistrc`, a `browserslist` file or the
+/// `browserslist` key of a `package.json`. Directories are searched upwards, `None` if no config
+/// is found. Without a section for `env`, the queries outside of sections are used, or `defaults`.
+pub fn read_browserslist_config(path: impl AsRef<Path>, env: &str) -> Result<Option<String>> {
+    let path = path.as_ref();
+    if path.is_file() {
+        return read_config_file(path, env);
+    }
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    for dir in path.ancestors() {
+        for name in [".browserslistrc", "browserslist", "package.json"] {
+            let file = dir.join(name);
+            if file.is_file() {
+                if let Some(query) = read_config_file(&file, env)? {
+                    return Ok(Some(query));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Query of a single config file, `None` for a `package.json` without `browserslist`
+fn read_config_file(path: &Path, env: &str) -> Result<Option<String>> {
+    let content = read_text_file(path)?;
+    let config_error = |message: String| Error::Config {
+        path: path.display().to_string(),
+        message,
+    };
+
+    let mut sections: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    if path.file_name().is_some_and(|n| n == "package.json") {
+        let json: Value =
+            serde_json::from_str(&content).map_err(|e| config_error(e.to_string()))?;
+        let queries = |value: &Value| -> Result<Vec<String>> {
+            match value {
+                Value::String(q) => Ok(vec![q.clone()]),
+                Value::Array(qs) => qs
+                    .iter()
+                    .map(|q| {
+                        q.as_str()
+                            .map(String::from)
+                            .ok_or_else(|| config_error("browserslist must contain strings".into()))
+                    })
+                    .collect(),
+                _ => Err(config_error(
+                    "browserslist must be a string or an array".into(),
+                )),
+            }
+        };
+        match json.get("browserslist") {
+            None => return Ok(None),
+            Some(Value::Object(envs)) => {
+                for (names, value) in envs {
+                    for name in names.split_whitespace() {
+                        sections.insert(name.to_string(), queries(value)?);
+                    }
+                }
+            }
+            Some(value) => {
+                sections.insert("defaults".into(), queries(value)?);
+            }
+        }
+    } else {
+        let mut current = vec!["defaults".to_string()];
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some(names) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = names.split_whitespace().map(String::from).collect();
+            } else if !line.is_empty() {
+                for name in &current {
+                    sections
+                        .entry(name.clone())
+                        .or_default()
+                        .push(line.to_string());
+                }
+            }
+        }
+    }
+
+    let queries = sections
+        .get(env)
+        .or_else(|| sections.get("defaults"))
+        .filter(|q| !q.is_empty());
+    if let Some(extends) = queries
+        .into_iter()
+        .flatten()
+        .find(|q| q.trim().to_lowercase().starts_with("extends "))
+    {
+        return Err(config_error(format!("{} is not supported", extends)));
+    }
+    Ok(Some(
+        queries.map_or_else(|| "defaults".to_string(), |q| q.join(", ")),
+    ))
+}
+
+fn browser(name: &str, version: &str) -> Browser {
+    Browser {
+        name: name.to_string(),
+        version: version.to_string(),
+    }
+}
+
+/// Components of the lower bound of a version like `16.6-16.7`, without trailing zeros.
+/// `None` for `all` and `TP`.
+fn version_key(version: &str) -> Option<Vec<u32>> {
+    let lower = version.split('-').next()?;
+    let mut key = lower
+        .split('.')
+        .map(|p| p.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    while key.last() == Some(&0) {
+        key.pop();
+    }
+    Some(key)
+}
+
+/// `true` if a version from the data is the queried one, `ios_saf 16.6` matches `16.6-16.7`
+fn version_matches(version: &str, query: &str) -> bool {
+    if version == query {
+        return true;
+    }
+    // `14-14.1` is a range of versions, unless the data has it as one version
+    if query.contains('-') {
+        return false;
+    }
+    let (Some(query), Some(lower)) = (version_key(query), version_key(version)) else {
+        return false;
+    };
+    let upper = version
+        .split_once('-')
+        .and_then(|(_, upper)| version_key(upper))
+        .unwrap_or_else(|| lower.clone());
+    lower <= query && query <= upper
+}
+
+/// `>=0.5` into its operator and value, `>` if there is no operator
+fn split_operator(query: &str) -> (&str, &str) {
+    for op in [">=", "<=", ">", "<"] {
+        if let Some(value) = query.strip_prefix(op) {
+            return (op, value.trim());
+        }
+    }
+    (">", query)
+}
+
+fn compare(op: &str, ordering: Option<std::cmp::Ordering>) -> bool {
+    use std::cmp::Ordering::*;
+    matches!(
+        (op, ordering),
+        (">", Some(Greater))
+            | (">=", Some(Greater | Equal))
+            | ("<", Some(Less))
+            | ("<=", Some(Less | Equal))
+    )
+}
+
+/// Days since 1970-01-01
+fn today() -> i64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    (seconds / 86400) as i64
+}
+
+/// Days since 1970-01-01 of a date like `2026-04-30`
+fn days_from_date(date: &str) -> Option<i64> {
+    let mut parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (parts.next()??, parts.next()??, parts.next()??);
+    // days from civil, http://howardhinnant.github.io/date_algorithms.html
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146097 + doe - 719468)
+}
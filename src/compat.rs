@@ -0,0 +1,535 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use crate::es_features::EsFeature;
+use crate::{read_text_file, Error, Result};
+
+/// First engine versions per feature, generated from MDN browser-compat-data.
+/// Hermes and QuickJS are not part of it and maintained by hand.
+const EMBEDDED: &str = include_str!("../data/compat.json");
+
+/// Javascript engines the compatibility table covers
+#[derive(
+    Display, EnumString, EnumIter, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum Engine {
+    Chrome,
+    Edge,
+    Firefox,
+    Safari,
+    #[strum(
+        to_string = "iOS Safari",
+        serialize = "ios_safari",
+        serialize = "safari_ios"
+    )]
+    IosSafari,
+    #[strum(to_string = "Node", serialize = "nodejs")]
+    Node,
+    Deno,
+    Bun,
+    Hermes,
+    #[strum(to_string = "QuickJS")]
+    QuickJs,
+}
+
+impl Engine {
+    /// Identifier in the compatibility table, the same as in browser-compat-data
+    pub fn id(&self) -> &'static str {
+        match self {
+            Engine::Chrome => "chrome",
+            Engine::Edge => "edge",
+            Engine::Firefox => "firefox",
+            Engine::Safari => "safari",
+            Engine::IosSafari => "safari_ios",
+            Engine::Node => "nodejs",
+            Engine::Deno => "deno",
+            Engine::Bun => "bun",
+            Engine::Hermes => "hermes",
+            Engine::QuickJs => "quickjs",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Engine> {
+        Engine::iter().find(|e| e.id() == id)
+    }
+
+    /// The oldest version in the table, required by code without any detected feature
+    fn oldest_version(&self) -> EngineVersion {
+        let version = match self {
+            Engine::Chrome => "1",
+            Engine::Edge => "12",
+            Engine::Firefox => "1",
+            Engine::Safari | Engine::IosSafari => "1",
+            Engine::Node => "0.10",
+            Engine::Deno | Engine::Bun => "1.0",
+            Engine::Hermes => "0.7",
+            Engine::QuickJs => "2019-07-09",
+        };
+        version.parse().expect("valid version")
+    }
+}
+
+impl Serialize for Engine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+/// Version of an engine like `14.1` or, for QuickJS, a release date like `2021-03-27`.
+/// Versions compare by their numeric components, `14` equals `14.0`.
+#[derive(Debug, Clone)]
+pub struct EngineVersion {
+    parts: Vec<u32>,
+    text: String,
+}
+
+impl EngineVersion {
+    /// Components without trailing zeros, used for comparisons
+    fn key(&self) -> &[u32] {
+        let len = self
+            .parts
+            .iter()
+            .rposition(|p| *p != 0)
+            .map_or(0, |i| i + 1);
+        &self.parts[..len]
+    }
+}
+
+impl FromStr for EngineVersion {
+    type Err = String;
+
+    /// Parses `14.1`, `2021-03-27` and ranges like `≤79` from browser-compat-data as their bound
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let text = s.trim().trim_start_matches('≤');
+        let parts = text
+            .split(['.', '-'])
+            .map(|p| p.parse::<u32>())
+            .collect::<std::result::Result<Vec<u32>, _>>()
+            .map_err(|_| format!("invalid engine version {}", s))?;
+        Ok(EngineVersion {
+            parts,
+            text: text.to_string(),
+        })
+    }
+}
+
+impl Display for EngineVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Serialize for EngineVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl PartialEq for EngineVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for EngineVersion {}
+
+impl Hash for EngineVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl PartialOrd for EngineVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EngineVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(other.key())
+    }
+}
+
+/// The first version of each engine that supports a feature, `None` if no version does
+#[derive(Debug, Clone)]
+pub struct CompatTable {
+    support: HashMap<EsFeature, HashMap<Engine, Option<EngineVersion>>>,
+}
+
+impl CompatTable {
+    /// The table compiled into the binary, parsed on first use
+    pub fn embedded() -> &'static CompatTable {
+        static TABLE: OnceLock<CompatTable> = OnceLock::new();
+        TABLE.get_or_init(|| {
+            CompatTable::from_json(EMBEDDED).expect("embedded compatibility data is valid")
+        })
+    }
+
+    /// Reads a table in the format of [`CompatTable::to_json`]
+    pub fn from_json(json: &str) -> std::result::Result<CompatTable, String> {
+        let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let features = value.as_object().ok_or("expected an object of features")?;
+        let mut support = HashMap::new();
+        for (feature, engines) in features {
+            let feature: EsFeature = feature
+                .parse()
+                .map_err(|_| format!("unknown feature {}", feature))?;
+            let engines = engines
+                .as_object()
+                .ok_or_else(|| format!("expected an object of engines for {}", feature))?;
+            let mut versions = HashMap::new();
+            for (engine, version) in engines {
+                let engine =
+                    Engine::from_id(engine).ok_or_else(|| format!("unknown engine {}", engine))?;
+                let version = match version {
+                    Value::Null => None,
+                    Value::String(v) => Some(v.parse()?),
+                    _ => return Err(format!("expected a version for {} in {}", engine, feature)),
+                };
+                versions.insert(engine, version);
+            }
+            support.insert(feature, versions);
+        }
+        Ok(CompatTable { support })
+    }
+
+    /// Updates the embedded table from the `data.json` of MDN browser-compat-data.
+    /// Engines and features browser-compat-data does not know keep their embedded versions.
+    pub fn from_bcd_file(path: impl AsRef<Path>) -> Result<CompatTable> {
+        let path = path.as_ref();
+        let json: Value =
+            serde_json::from_str(&read_text_file(path)?).map_err(|e| Error::Config {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut table = CompatTable::embedded().clone();
+        for feature in EsFeature::iter() {
+            let entries: Vec<&Map<String, Value>> = bcd_keys(feature)
+                .iter()
+                .filter_map(|key| bcd_support(&json, key))
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+            let versions = table.support.entry(feature).or_default();
+            for engine in Engine::iter() {
+                // every key of the feature must be known, the newest version wins
+                let known: Option<Vec<Option<EngineVersion>>> = entries
+                    .iter()
+                    .map(|support| support.get(engine.id()).and_then(bcd_version))
+                    .collect();
+                if let Some(known) = known {
+                    let version = known.into_iter().collect::<Option<Vec<_>>>();
+                    versions.insert(engine, version.and_then(|v| v.into_iter().max()));
+                }
+            }
+        }
+        Ok(table)
+    }
+
+    /// The table as JSON, with features in edition order. This is the format of the embedded data.
+    pub fn to_json(&self) -> String {
+        let table: BTreeMap<EsFeature, BTreeMap<Engine, &Option<EngineVersion>>> = self
+            .support
+            .iter()
+            .map(|(feature, versions)| (*feature, versions.iter().map(|(e, v)| (*e, v)).collect()))
+            .collect();
+        format!("{}\n", serde_json::to_string_pretty(&table).unwrap())
+    }
+
+    /// The first version of `engine` that supports `feature`, `None` if no version does
+    pub fn first_version(&self, feature: EsFeature, engine: Engine) -> Option<&EngineVersion> {
+        self.support
+            .get(&feature)
+            .and_then(|versions| versions.get(&engine))
+            .and_then(Option::as_ref)
+    }
+
+    /// The minimum version of every engine that supports all features, `None` for engines that
+    /// lack at least one of them
+    pub fn min_versions(
+        &self,
+        features: impl IntoIterator<Item = EsFeature>,
+    ) -> BTreeMap<Engine, Option<EngineVersion>> {
+        let features: Vec<EsFeature> = features.into_iter().collect();
+        Engine::iter()
+            .map(|engine| {
+                let min_version = features
+                    .iter()
+                    .map(|feature| self.first_version(*feature, engine))
+                    .try_fold(None, |max: Option<&EngineVersion>, version| {
+                        let version = version?;
+                        Some(Some(max.map_or(version, |max| max.max(version))))
+                    })
+                    .map(|max| max.cloned().unwrap_or_else(|| engine.oldest_version()));
+                (engine, min_version)
+            })
+            .collect()
+    }
+}
+
+/// The `support` object of a browser-compat-data entry. Keys are dotted paths, but names like
+/// `for...of` contain dots themselves, so the longest matching name is taken at each level.
+fn bcd_support<'a>(json: &'a Value, key: &str) -> Option<&'a Map<String, Value>> {
+    let segments: Vec<&str> = key.split('.').collect();
+    let mut node = json;
+    let mut i = 0;
+    while i < segments.len() {
+        let (next, len) = (i + 1..=segments.len())
+            .rev()
+            .find_map(|end| node.get(segments[i..end].join(".")).map(|n| (n, end - i)))?;
+        node = next;
+        i += len;
+    }
+    node.get("__compat")?.get("support")?.as_object()
+}
+
+/// Version from a browser-compat-data support statement. The outer `None` means unknown,
+/// the inner `None` that the engine lacks the feature. Flagged, prefixed, partial and removed
+/// implementations do not count.
+fn bcd_version(statement: &Value) -> Option<Option<EngineVersion>> {
+    let statements = match statement {
+        Value::Array(statements) => statements.iter().collect(),
+        statement => vec![statement],
+    };
+    let usable = statements.into_iter().find(|s| {
+        ["flags", "prefix", "alternative_name", "version_removed"]
+            .iter()
+            .all(|k| s.get(k).is_none_or(|v| v.is_null() || v == false))
+            && s.get("partial_implementation") != Some(&Value::Bool(true))
+    });
+    let Some(usable) = usable else {
+        return Some(None);
+    };
+    match usable.get("version_added")? {
+        Value::String(v) if v == "preview" => Some(None),
+        Value::String(v) => v.parse().ok().map(Some),
+        Value::Bool(false) => Some(None),
+        _ => None,
+    }
+}
+
+/// Entries of browser-compat-data that make up a feature
+fn bcd_keys(feature: EsFeature) -> &'static [&'static str] {
+    match feature {
+        EsFeature::StrictMode | EsFeature::ForInMechanics => &[],
+        EsFeature::PropertyAccessors => &["javascript.functions.get", "javascript.functions.set"],
+        EsFeature::Es5BuiltIns => &[
+            "javascript.builtins.Object.keys",
+            "javascript.builtins.JSON",
+        ],
+        EsFeature::ArrowFunctions => &["javascript.functions.arrow_functions"],
+        EsFeature::Classes => &["javascript.classes"],
+        EsFeature::LetConst => &["javascript.statements.let", "javascript.statements.const"],
+        EsFeature::TemplateLiterals | EsFeature::TaggedTemplateLiterals => {
+            &["javascript.grammar.template_literals"]
+        }
+        EsFeature::Destructuring => &["javascript.operators.destructuring"],
+        EsFeature::DefaultParameters => &["javascript.functions.default_parameters"],
+        EsFeature::RestParameters => &["javascript.functions.rest_parameters"],
+        EsFeature::SpreadOperator => &["javascript.operators.spread"],
+        EsFeature::ShorthandProperties => {
+            &["javascript.operators.object_initializer.shorthand_property_names"]
+        }
+        EsFeature::ComputedProperties => {
+            &["javascript.operators.object_initializer.computed_property_names"]
+        }
+        EsFeature::ForOf => &["javascript.statements.for...of"],
+        EsFeature::Generators => &["javascript.statements.function*"],
+        EsFeature::NewTarget => &["javascript.operators.new_target"],
+        EsFeature::RegExpUnicodeFlag => &["javascript.builtins.RegExp.unicode"],
+        EsFeature::RegExpStickyFlag => &["javascript.builtins.RegExp.sticky"],
+        EsFeature::Modules => &[
+            "javascript.statements.import",
+            "javascript.statements.export",
+        ],
+        EsFeature::KeyedCollections => &[
+            "javascript.builtins.Map",
+            "javascript.builtins.Set",
+            "javascript.builtins.WeakMap",
+            "javascript.builtins.WeakSet",
+        ],
+        EsFeature::Symbols => &["javascript.builtins.Symbol"],
+        EsFeature::Proxies => &["javascript.builtins.Proxy"],
+        EsFeature::Reflect => &["javascript.builtins.Reflect"],
+        EsFeature::Promises => &["javascript.builtins.Promise"],
+        EsFeature::TypedArrays => &[
+            "javascript.builtins.ArrayBuffer",
+            "javascript.builtins.TypedArray",
+        ],
+        EsFeature::Es2015BuiltIns => &[
+            "javascript.builtins.Object.assign",
+            "javascript.builtins.Array.from",
+        ],
+        EsFeature::ArrayPrototypeIncludes => &["javascript.builtins.Array.includes"],
+        EsFeature::ExponentiationOperator => &["javascript.operators.exponentiation"],
+        EsFeature::ObjectValuesEntries => &[
+            "javascript.builtins.Object.values",
+            "javascript.builtins.Object.entries",
+        ],
+        EsFeature::StringPadding => &[
+            "javascript.builtins.String.padStart",
+            "javascript.builtins.String.padEnd",
+        ],
+        EsFeature::ObjectGetOwnPropertyDescriptors => {
+            &["javascript.builtins.Object.getOwnPropertyDescriptors"]
+        }
+        EsFeature::TrailingCommasInFunctionParameterListsAndCalls => {
+            &["javascript.grammar.trailing_commas.trailing_commas_in_functions"]
+        }
+        EsFeature::AsyncFunctions => &["javascript.statements.async_function"],
+        EsFeature::SharedMemoryAndAtomics => &["javascript.builtins.Atomics"],
+        EsFeature::LiftingTemplateLiteralRestriction => {
+            &["javascript.grammar.template_literals.template_literal_revision"]
+        }
+        EsFeature::SDotAllFlagForRegularExpressions => &["javascript.builtins.RegExp.dotAll"],
+        EsFeature::RegExpNamedCaptureGroups => {
+            &["javascript.regular_expressions.named_capturing_group"]
+        }
+        EsFeature::RestSpreadProperties => &[
+            "javascript.operators.destructuring.rest_in_objects",
+            "javascript.operators.spread.spread_in_object_literals",
+        ],
+        EsFeature::RegExpLookbehindAssertions => {
+            &["javascript.regular_expressions.lookbehind_assertion"]
+        }
+        EsFeature::RegExpUnicodePropertyEscapes => {
+            &["javascript.regular_expressions.unicode_character_class_escape"]
+        }
+        EsFeature::PromisePrototypeFinally => &["javascript.builtins.Promise.finally"],
+        EsFeature::AsynchronousIteration => &["javascript.statements.for_await...of"],
+        EsFeature::SymbolAsyncIterator => &["javascript.builtins.Symbol.asyncIterator"],
+        EsFeature::OptionalCatchBinding => {
+            &["javascript.statements.try...catch.optional_catch_binding"]
+        }
+        EsFeature::JSONSuperset => &["javascript.builtins.JSON.json_superset"],
+        EsFeature::SymbolPrototypeDescription => &["javascript.builtins.Symbol.description"],
+        EsFeature::ObjectFromEntries => &["javascript.builtins.Object.fromEntries"],
+        EsFeature::StringPrototypeTrimStartTrimEnd => &[
+            "javascript.builtins.String.trimStart",
+            "javascript.builtins.String.trimEnd",
+        ],
+        EsFeature::ArrayPrototypeFlatFlatMap => &[
+            "javascript.builtins.Array.flat",
+            "javascript.builtins.Array.flatMap",
+        ],
+        EsFeature::StringPrototypeMatchAll => &["javascript.builtins.String.matchAll"],
+        EsFeature::Import => &["javascript.operators.import"],
+        // the literal shipped together with the constructor
+        EsFeature::BigInt | EsFeature::BigIntLiterals => &["javascript.builtins.BigInt"],
+        EsFeature::PromiseAllSettled => &["javascript.builtins.Promise.allSettled"],
+        EsFeature::GlobalThis => &["javascript.builtins.globalThis"],
+        EsFeature::OptionalChaining => &["javascript.operators.optional_chaining"],
+        EsFeature::NullishCoalescingOperator => &["javascript.operators.nullish_coalescing"],
+        EsFeature::ImportMeta => &["javascript.operators.import_meta"],
+        EsFeature::StringPrototypeReplaceAll => &["javascript.builtins.String.replaceAll"],
+        EsFeature::PromiseAny => &["javascript.builtins.Promise.any"],
+        EsFeature::WeakRefs => &[
+            "javascript.builtins.WeakRef",
+            "javascript.builtins.FinalizationRegistry",
+        ],
+        EsFeature::LogicalAssignmentOperators => &[
+            "javascript.operators.logical_and_assignment",
+            "javascript.operators.logical_or_assignment",
+            "javascript.operators.nullish_coalescing_assignment",
+        ],
+        EsFeature::NumericSeparators => &["javascript.grammar.numeric_separators"],
+        EsFeature::ClassFields => &[
+            "javascript.classes.public_class_fields",
+            "javascript.classes.private_class_fields",
+        ],
+        EsFeature::RegExpMatchIndices => &["javascript.builtins.RegExp.hasIndices"],
+        EsFeature::TopLevelAwait => &["javascript.operators.await.top_level"],
+        EsFeature::ErgonomicBrandChecksForPrivateFields => {
+            &["javascript.classes.private_class_fields_in"]
+        }
+        EsFeature::At => &[
+            "javascript.builtins.Array.at",
+            "javascript.builtins.String.at",
+        ],
+        EsFeature::AccessibleObjectPrototypeHasOwnProperty => {
+            &["javascript.builtins.Object.hasOwn"]
+        }
+        EsFeature::ClassStaticBlock => &["javascript.classes.static_initialization_blocks"],
+        EsFeature::ErrorCause => &["javascript.builtins.Error.Error.options_cause_parameter"],
+        EsFeature::ArrayFindFromLast => &[
+            "javascript.builtins.Array.findLast",
+            "javascript.builtins.Array.findLastIndex",
+        ],
+        EsFeature::HashbangGrammar => &["javascript.grammar.hashbang_comments"],
+        EsFeature::SymbolsAsWeakMapKeys => &["javascript.builtins.WeakMap.symbol_as_keys"],
+        EsFeature::ChangeArrayByCopy => &[
+            "javascript.builtins.Array.toReversed",
+            "javascript.builtins.Array.toSorted",
+            "javascript.builtins.Array.toSpliced",
+            "javascript.builtins.Array.with",
+        ],
+        EsFeature::WellFormedUnicodeStrings => &[
+            "javascript.builtins.String.isWellFormed",
+            "javascript.builtins.String.toWellFormed",
+        ],
+        EsFeature::AtomicsWaitAsync => &["javascript.builtins.Atomics.waitAsync"],
+        EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings => {
+            &["javascript.builtins.RegExp.unicodeSets"]
+        }
+        EsFeature::ResizableAndGrowableArrayBuffers => &[
+            "javascript.builtins.ArrayBuffer.resize",
+            "javascript.builtins.SharedArrayBuffer.grow",
+        ],
+        EsFeature::ArrayGrouping => &[
+            "javascript.builtins.Object.groupBy",
+            "javascript.builtins.Map.groupBy",
+        ],
+        EsFeature::PromiseWithResolvers => &["javascript.builtins.Promise.withResolvers"],
+        EsFeature::ArrayBufferTransfer => &["javascript.builtins.ArrayBuffer.transfer"],
+        EsFeature::DuplicateNamedCaptureGroups => &[
+            "javascript.regular_expressions.named_capturing_group.duplicate_named_capturing_groups",
+        ],
+        EsFeature::RegExpModifiers => &["javascript.regular_expressions.modifier"],
+        EsFeature::NetSetMethods => &[
+            "javascript.builtins.Set.union",
+            "javascript.builtins.Set.intersection",
+            "javascript.builtins.Set.difference",
+        ],
+        EsFeature::IteratorHelpers => &[
+            "javascript.builtins.Iterator.map",
+            "javascript.builtins.Iterator.from",
+        ],
+        EsFeature::ImportAttributes => &["javascript.statements.import.import_attributes"],
+        EsFeature::JsonModules => &["javascript.statements.import.import_attributes.type_json"],
+        EsFeature::RegExpEscaping => &["javascript.builtins.RegExp.escape"],
+        EsFeature::Float16Array => &["javascript.builtins.Float16Array"],
+        EsFeature::PromiseTry => &["javascript.builtins.Promise.try"],
+        EsFeature::ExplicitResourceManagement => &["javascript.statements.using"],
+        EsFeature::ExplicitResourceManagementBuiltIns => &[
+            "javascript.builtins.DisposableStack",
+            "javascript.builtins.Symbol.dispose",
+        ],
+        EsFeature::ArrayFromAsync => &["javascript.builtins.Array.fromAsync"],
+        EsFeature::ErrorIsError => &["javascript.builtins.Error.isError"],
+        EsFeature::MathSumPrecise => &["javascript.builtins.Math.sumPrecise"],
+        EsFeature::Uint8ArrayBase64 => &[
+            "javascript.builtins.Uint8Array.fromBase64",
+            "javascript.builtins.Uint8Array.toBase64",
+        ],
+        EsFeature::Temporal => &["javascript.builtins.Temporal"],
+        EsFeature::Decorators
+        | EsFeature::ImportDefer
+        | EsFeature::SourcePhaseImports
+        | EsFeature::ShadowRealm
+        | EsFeature::AsyncContext => &[],
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+use swc_common::{SourceMap, Span, Spanned};
+
+use crate::location::Location;
+
+/// A syntax error reported by the parser
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub location: Location,
+    /// Byte offset of the start of the error in the source
+    pub start: usize,
+    /// Byte offset of the end of the error in the source
+    pub end: usize,
+    /// The offending source line with the error underlined
+    pub code_frame: String,
+    /// `true` if the parser recovered from the error and continued
+    pub recoverable: bool,
+}
+
+impl Diagnostic {
+    pub(crate) fn from_parser_error(
+        cm: &SourceMap,
+        error: &swc_ecma_parser::error::Error,
+        recoverable: bool,
+    ) -> Self {
+        let span = error.span();
+        let file = cm.lookup_char_pos(span.lo).file;
+        Diagnostic {
+            message: error.kind().msg().to_string(),
+            location: Location::from_span(cm, span),
+            start: (span.lo - file.start_pos).0 as usize,
+            end: (span.hi - file.start_pos).0 as usize,
+            code_frame: code_frame(cm, span),
+            recoverable,
+        }
+    }
+
+    /// An error that is not tied to a position, reported at the start of the file
+    pub(crate) fn from_error(file: &str, error: &crate::Error) -> Self {
+        Diagnostic {
+            message: error.to_string(),
+            location: Location {
+                file: file.to_string(),
+                line: 1,
+                column: 1,
+            },
+            start: 0,
+            end: 0,
+            code_frame: String::new(),
+            recoverable: false,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}\n{}",
+            self.location, self.message, self.code_frame
+        )
+    }
+}
+
+/// Renders the first line of the span with a marker below the affected columns
+fn code_frame(cm: &SourceMap, span: Span) -> String {
+    let lo = cm.lookup_char_pos(span.lo);
+    let hi = cm.lookup_char_pos(span.hi);
+    let line = lo
+        .file
+        .get_line(lo.line - 1)
+        .map(|l| l.trim_end().to_string())
+        .unwrap_or_default();
+    let width = if hi.line == lo.line {
+        (hi.col.0 - lo.col.0).max(1)
+    } else {
+        line.chars().count().saturating_sub(lo.col.0).max(1)
+    };
+
+    let number = lo.line.to_string();
+    let gutter = " ".repeat(number.len());
+    format!(
+        "{} | {}\n{} | {}{}",
+        number,
+        line,
+        gutter,
+        " ".repeat(lo.col.0),
+        "^".repeat(width)
+    )
+}
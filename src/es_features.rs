@@ -1,106 +1,413 @@
-use strum_macros::Display;
+use serde::Serialize;
+use strum_macros::{Display, EnumIter, EnumString};
 
 use crate::es_version::EsVersion;
 
-/// Stage 4 proposals
+/// ES5 additions, Stage 4 proposals and, only detected on request, Stage 2.7/3 proposals
 /// https://github.com/tc39/proposals/blob/HEAD/finished-proposals.md
-#[derive(Hash, Debug, Display, Eq, Copy, Clone, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Hash,
+    Debug,
+    Display,
+    EnumString,
+    EnumIter,
+    Serialize,
+    Eq,
+    Copy,
+    Clone,
+    Ord,
+    PartialEq,
+    PartialOrd,
+)]
 pub enum EsFeature {
-    // ArrayPrototypeIncludes,
+    // ES5
+    StrictMode,
+    PropertyAccessors,
+    Es5BuiltIns,
+
+    // ES2015
+    ArrowFunctions,
+    Classes,
+    LetConst,
+    TemplateLiterals,
+    TaggedTemplateLiterals,
+    Destructuring,
+    DefaultParameters,
+    RestParameters,
+    SpreadOperator,
+    ShorthandProperties,
+    ComputedProperties,
+    ForOf,
+    Generators,
+    NewTarget,
+    RegExpUnicodeFlag,
+    RegExpStickyFlag,
+    Modules,
+    KeyedCollections,
+    Symbols,
+    Proxies,
+    Reflect,
+    Promises,
+    TypedArrays,
+    Es2015BuiltIns,
+
+    // Stage 4 proposals
+    ArrayPrototypeIncludes,
     ExponentiationOperator,
     ObjectValuesEntries,
-    // StringPadding,
+    StringPadding,
     ObjectGetOwnPropertyDescriptors,
-    // TrailingCommasInFunctionParameterListsAndCalls,
+    TrailingCommasInFunctionParameterListsAndCalls,
     AsyncFunctions,
     SharedMemoryAndAtomics,
-    // LiftingTemplateLiteralRestriction,
+    LiftingTemplateLiteralRestriction,
     SDotAllFlagForRegularExpressions,
-    // RegExpNamedCaptureGroups,
+    RegExpNamedCaptureGroups,
     RestSpreadProperties,
     RegExpLookbehindAssertions,
     RegExpUnicodePropertyEscapes,
-    // PromisePrototypeFinally,
-    // AsynchronousIteration,
+    PromisePrototypeFinally,
+    AsynchronousIteration,
+    SymbolAsyncIterator,
     OptionalCatchBinding,
-    // JSONSuperset,
-    // SymbolPrototypeDescription,
+    JSONSuperset,
+    SymbolPrototypeDescription,
     // FunctionPrototypeToStringRevision,
     ObjectFromEntries,
     // WellFormedJSONStringify,
-    // StringPrototypeTrimStartTrimEnd,
-    // ArrayPrototypeFlatFlatMap,
-    // StringPrototypeMatchAll,
-    // Import,
+    StringPrototypeTrimStartTrimEnd,
+    ArrayPrototypeFlatFlatMap,
+    StringPrototypeMatchAll,
+    Import,
     BigInt,
+    BigIntLiterals,
     PromiseAllSettled,
     GlobalThis,
     ForInMechanics,
     OptionalChaining,
     NullishCoalescingOperator,
-    // ImportMeta,
-    // StringPrototypeReplaceAll,
+    ImportMeta,
+    StringPrototypeReplaceAll,
     PromiseAny,
-    // WeakRefs,
+    WeakRefs,
     LogicalAssignmentOperators,
     NumericSeparators,
     ClassFields,
     RegExpMatchIndices,
     TopLevelAwait,
     ErgonomicBrandChecksForPrivateFields,
-    // At,
+    At,
     AccessibleObjectPrototypeHasOwnProperty,
     ClassStaticBlock,
-    // ErrorCause,
-    // ArrayFindFromLast,
-    // HashbangGrammar,
-    // SymbolsAsWeakMapKeys,
-    // ChangeArrayByCopy,
-    // WellFormedUnicodeStrings,
+    ErrorCause,
+    ArrayFindFromLast,
+    HashbangGrammar,
+    SymbolsAsWeakMapKeys,
+    ChangeArrayByCopy,
+    WellFormedUnicodeStrings,
     AtomicsWaitAsync,
     RegexpVFlagWithSetNotationAndPropertiesOfStrings,
-    // ResizableAndGrowableArrayBuffers,
+    ResizableAndGrowableArrayBuffers,
     ArrayGrouping,
     PromiseWithResolvers,
-    // ArrayBufferTransfer,
-    // DuplicateNamedCaptureGroups,
-    // NetSetMethods,
+    ArrayBufferTransfer,
+    DuplicateNamedCaptureGroups,
+    RegExpModifiers,
+    NetSetMethods,
+    IteratorHelpers,
+    ImportAttributes,
+    JsonModules,
+    RegExpEscaping,
+    Float16Array,
+    PromiseTry,
+    ExplicitResourceManagement,
+    ExplicitResourceManagementBuiltIns,
+    ArrayFromAsync,
+    ErrorIsError,
+    MathSumPrecise,
+    Uint8ArrayBase64,
+
+    // Stage 2.7/3 proposals, not part of any edition
+    Decorators,
+    ImportDefer,
+    SourcePhaseImports,
+    Temporal,
+    ShadowRealm,
+    AsyncContext,
+}
+
+/// How an older engine can be made to run a feature
+#[derive(
+    Display,
+    EnumString,
+    EnumIter,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureKind {
+    /// Grammar and semantics of the language, only transpiling removes them
+    Syntax,
+    /// Objects and methods of the standard library, they can be polyfilled
+    BuiltIn,
+    /// Regular expression flags and patterns and tokens like numeric separators or a hashbang,
+    /// they need a transpiler that rewrites literals
+    Lexical,
 }
 
 impl EsFeature {
-    /// Maps language features to ECMAScript versions
+    /// `true` for proposals that are not part of any edition yet
+    pub fn is_proposal(&self) -> bool {
+        matches!(
+            self,
+            EsFeature::Decorators
+                | EsFeature::ImportDefer
+                | EsFeature::SourcePhaseImports
+                | EsFeature::Temporal
+                | EsFeature::ShadowRealm
+                | EsFeature::AsyncContext
+        )
+    }
+
+    /// `true` for objects and methods of the standard library, which code can test for at runtime
+    /// and polyfill, unlike syntax
+    pub fn is_built_in(&self) -> bool {
+        self.kind() == FeatureKind::BuiltIn
+    }
+
+    /// Whether the feature is syntax, a built-in or part of a regular expression or token
+    pub fn kind(&self) -> FeatureKind {
+        match self {
+            EsFeature::Es5BuiltIns
+            | EsFeature::KeyedCollections
+            | EsFeature::Symbols
+            | EsFeature::Proxies
+            | EsFeature::Reflect
+            | EsFeature::Promises
+            | EsFeature::TypedArrays
+            | EsFeature::Es2015BuiltIns
+            | EsFeature::ArrayPrototypeIncludes
+            | EsFeature::ObjectValuesEntries
+            | EsFeature::StringPadding
+            | EsFeature::ObjectGetOwnPropertyDescriptors
+            | EsFeature::SharedMemoryAndAtomics
+            | EsFeature::PromisePrototypeFinally
+            | EsFeature::SymbolPrototypeDescription
+            | EsFeature::ObjectFromEntries
+            | EsFeature::StringPrototypeTrimStartTrimEnd
+            | EsFeature::ArrayPrototypeFlatFlatMap
+            | EsFeature::StringPrototypeMatchAll
+            | EsFeature::SymbolAsyncIterator
+            | EsFeature::BigInt
+            | EsFeature::PromiseAllSettled
+            | EsFeature::GlobalThis
+            | EsFeature::StringPrototypeReplaceAll
+            | EsFeature::PromiseAny
+            | EsFeature::WeakRefs
+            | EsFeature::At
+            | EsFeature::AccessibleObjectPrototypeHasOwnProperty
+            | EsFeature::ErrorCause
+            | EsFeature::ArrayFindFromLast
+            | EsFeature::SymbolsAsWeakMapKeys
+            | EsFeature::ChangeArrayByCopy
+            | EsFeature::WellFormedUnicodeStrings
+            | EsFeature::AtomicsWaitAsync
+            | EsFeature::ResizableAndGrowableArrayBuffers
+            | EsFeature::ArrayGrouping
+            | EsFeature::PromiseWithResolvers
+            | EsFeature::ArrayBufferTransfer
+            | EsFeature::NetSetMethods
+            | EsFeature::IteratorHelpers
+            | EsFeature::RegExpEscaping
+            | EsFeature::Float16Array
+            | EsFeature::PromiseTry
+            | EsFeature::ExplicitResourceManagementBuiltIns
+            | EsFeature::ArrayFromAsync
+            | EsFeature::ErrorIsError
+            | EsFeature::MathSumPrecise
+            | EsFeature::Uint8ArrayBase64
+            | EsFeature::Temporal
+            | EsFeature::ShadowRealm
+            | EsFeature::AsyncContext => FeatureKind::BuiltIn,
+            EsFeature::RegExpUnicodeFlag
+            | EsFeature::RegExpStickyFlag
+            | EsFeature::LiftingTemplateLiteralRestriction
+            | EsFeature::SDotAllFlagForRegularExpressions
+            | EsFeature::RegExpNamedCaptureGroups
+            | EsFeature::RegExpLookbehindAssertions
+            | EsFeature::RegExpUnicodePropertyEscapes
+            | EsFeature::JSONSuperset
+            | EsFeature::BigIntLiterals
+            | EsFeature::NumericSeparators
+            | EsFeature::RegExpMatchIndices
+            | EsFeature::HashbangGrammar
+            | EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings
+            | EsFeature::DuplicateNamedCaptureGroups
+            | EsFeature::RegExpModifiers => FeatureKind::Lexical,
+            EsFeature::StrictMode
+            | EsFeature::PropertyAccessors
+            | EsFeature::ArrowFunctions
+            | EsFeature::Classes
+            | EsFeature::LetConst
+            | EsFeature::TemplateLiterals
+            | EsFeature::TaggedTemplateLiterals
+            | EsFeature::Destructuring
+            | EsFeature::DefaultParameters
+            | EsFeature::RestParameters
+            | EsFeature::SpreadOperator
+            | EsFeature::ShorthandProperties
+            | EsFeature::ComputedProperties
+            | EsFeature::ForOf
+            | EsFeature::Generators
+            | EsFeature::NewTarget
+            | EsFeature::Modules
+            | EsFeature::ExponentiationOperator
+            | EsFeature::TrailingCommasInFunctionParameterListsAndCalls
+            | EsFeature::AsyncFunctions
+            | EsFeature::RestSpreadProperties
+            | EsFeature::AsynchronousIteration
+            | EsFeature::OptionalCatchBinding
+            | EsFeature::Import
+            | EsFeature::ForInMechanics
+            | EsFeature::OptionalChaining
+            | EsFeature::NullishCoalescingOperator
+            | EsFeature::ImportMeta
+            | EsFeature::LogicalAssignmentOperators
+            | EsFeature::ClassFields
+            | EsFeature::TopLevelAwait
+            | EsFeature::ErgonomicBrandChecksForPrivateFields
+            | EsFeature::ClassStaticBlock
+            | EsFeature::ImportAttributes
+            | EsFeature::JsonModules
+            | EsFeature::ExplicitResourceManagement
+            | EsFeature::Decorators
+            | EsFeature::ImportDefer
+            | EsFeature::SourcePhaseImports => FeatureKind::Syntax,
+        }
+    }
+
+    /// The edition that introduced the feature, `None` for proposals
+    pub fn edition(&self) -> Option<EsVersion> {
+        (!self.is_proposal()).then(|| self.version())
+    }
+
+    /// Maps language features to ECMAScript versions.
+    /// Proposals map to `ESNext`, use [`EsFeature::edition`] to tell them apart.
     pub fn version(&self) -> EsVersion {
         match self {
-            EsFeature::ExponentiationOperator => EsVersion::ES7,
-            EsFeature::ObjectValuesEntries => EsVersion::ES8,
-            EsFeature::ObjectGetOwnPropertyDescriptors => EsVersion::ES8,
-            EsFeature::AsyncFunctions => EsVersion::ES8,
-            EsFeature::SharedMemoryAndAtomics => EsVersion::ES8,
-            EsFeature::SDotAllFlagForRegularExpressions => EsVersion::ES9,
-            EsFeature::RestSpreadProperties => EsVersion::ES9,
-            EsFeature::RegExpLookbehindAssertions => EsVersion::ES9,
-            EsFeature::RegExpUnicodePropertyEscapes => EsVersion::ES9,
-            EsFeature::OptionalCatchBinding => EsVersion::ES10,
-            EsFeature::ObjectFromEntries => EsVersion::ES10,
-            EsFeature::BigInt => EsVersion::ES11,
-            EsFeature::PromiseAllSettled => EsVersion::ES11,
-            EsFeature::GlobalThis => EsVersion::ES11,
-            EsFeature::ForInMechanics => EsVersion::ES11,
-            EsFeature::OptionalChaining => EsVersion::ES11,
-            EsFeature::NullishCoalescingOperator => EsVersion::ES11,
-            EsFeature::PromiseAny => EsVersion::ES12,
-            EsFeature::LogicalAssignmentOperators => EsVersion::ES12,
-            EsFeature::NumericSeparators => EsVersion::ES12,
-            EsFeature::ClassFields => EsVersion::ES13,
-            EsFeature::RegExpMatchIndices => EsVersion::ES13,
-            EsFeature::TopLevelAwait => EsVersion::ES13,
-            EsFeature::ErgonomicBrandChecksForPrivateFields => EsVersion::ES13,
-            EsFeature::AccessibleObjectPrototypeHasOwnProperty => EsVersion::ES13,
-            EsFeature::ClassStaticBlock => EsVersion::ES13,
-            EsFeature::AtomicsWaitAsync => EsVersion::ES15,
-            EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings => EsVersion::ES15,
-            EsFeature::ArrayGrouping => EsVersion::ES15,
-            EsFeature::PromiseWithResolvers => EsVersion::ES15,
+            EsFeature::StrictMode => EsVersion::ES5,
+            EsFeature::PropertyAccessors => EsVersion::ES5,
+            EsFeature::Es5BuiltIns => EsVersion::ES5,
+            EsFeature::ArrowFunctions => EsVersion::ES2015,
+            EsFeature::Classes => EsVersion::ES2015,
+            EsFeature::LetConst => EsVersion::ES2015,
+            EsFeature::TemplateLiterals => EsVersion::ES2015,
+            EsFeature::TaggedTemplateLiterals => EsVersion::ES2015,
+            EsFeature::Destructuring => EsVersion::ES2015,
+            EsFeature::DefaultParameters => EsVersion::ES2015,
+            EsFeature::RestParameters => EsVersion::ES2015,
+            EsFeature::SpreadOperator => EsVersion::ES2015,
+            EsFeature::ShorthandProperties => EsVersion::ES2015,
+            EsFeature::ComputedProperties => EsVersion::ES2015,
+            EsFeature::ForOf => EsVersion::ES2015,
+            EsFeature::Generators => EsVersion::ES2015,
+            EsFeature::NewTarget => EsVersion::ES2015,
+            EsFeature::RegExpUnicodeFlag => EsVersion::ES2015,
+            EsFeature::RegExpStickyFlag => EsVersion::ES2015,
+            EsFeature::Modules => EsVersion::ES2015,
+            EsFeature::KeyedCollections => EsVersion::ES2015,
+            EsFeature::Symbols => EsVersion::ES2015,
+            EsFeature::Proxies => EsVersion::ES2015,
+            EsFeature::Reflect => EsVersion::ES2015,
+            EsFeature::Promises => EsVersion::ES2015,
+            EsFeature::TypedArrays => EsVersion::ES2015,
+            EsFeature::Es2015BuiltIns => EsVersion::ES2015,
+            EsFeature::ArrayPrototypeIncludes => EsVersion::ES2016,
+            EsFeature::ExponentiationOperator => EsVersion::ES2016,
+            EsFeature::ObjectValuesEntries => EsVersion::ES2017,
+            EsFeature::StringPadding => EsVersion::ES2017,
+            EsFeature::ObjectGetOwnPropertyDescriptors => EsVersion::ES2017,
+            EsFeature::TrailingCommasInFunctionParameterListsAndCalls => EsVersion::ES2017,
+            EsFeature::AsyncFunctions => EsVersion::ES2017,
+            EsFeature::SharedMemoryAndAtomics => EsVersion::ES2017,
+            EsFeature::LiftingTemplateLiteralRestriction => EsVersion::ES2018,
+            EsFeature::SDotAllFlagForRegularExpressions => EsVersion::ES2018,
+            EsFeature::RestSpreadProperties => EsVersion::ES2018,
+            EsFeature::RegExpNamedCaptureGroups => EsVersion::ES2018,
+            EsFeature::RegExpLookbehindAssertions => EsVersion::ES2018,
+            EsFeature::RegExpUnicodePropertyEscapes => EsVersion::ES2018,
+            EsFeature::PromisePrototypeFinally => EsVersion::ES2018,
+            EsFeature::AsynchronousIteration => EsVersion::ES2018,
+            EsFeature::SymbolAsyncIterator => EsVersion::ES2018,
+            EsFeature::OptionalCatchBinding => EsVersion::ES2019,
+            EsFeature::JSONSuperset => EsVersion::ES2019,
+            EsFeature::SymbolPrototypeDescription => EsVersion::ES2019,
+            EsFeature::ObjectFromEntries => EsVersion::ES2019,
+            EsFeature::StringPrototypeTrimStartTrimEnd => EsVersion::ES2019,
+            EsFeature::ArrayPrototypeFlatFlatMap => EsVersion::ES2019,
+            EsFeature::StringPrototypeMatchAll => EsVersion::ES2020,
+            EsFeature::Import => EsVersion::ES2020,
+            EsFeature::BigInt => EsVersion::ES2020,
+            EsFeature::BigIntLiterals => EsVersion::ES2020,
+            EsFeature::PromiseAllSettled => EsVersion::ES2020,
+            EsFeature::GlobalThis => EsVersion::ES2020,
+            EsFeature::ForInMechanics => EsVersion::ES2020,
+            EsFeature::OptionalChaining => EsVersion::ES2020,
+            EsFeature::NullishCoalescingOperator => EsVersion::ES2020,
+            EsFeature::ImportMeta => EsVersion::ES2020,
+            EsFeature::StringPrototypeReplaceAll => EsVersion::ES2021,
+            EsFeature::PromiseAny => EsVersion::ES2021,
+            EsFeature::WeakRefs => EsVersion::ES2021,
+            EsFeature::LogicalAssignmentOperators => EsVersion::ES2021,
+            EsFeature::NumericSeparators => EsVersion::ES2021,
+            EsFeature::ClassFields => EsVersion::ES2022,
+            EsFeature::RegExpMatchIndices => EsVersion::ES2022,
+            EsFeature::TopLevelAwait => EsVersion::ES2022,
+            EsFeature::ErgonomicBrandChecksForPrivateFields => EsVersion::ES2022,
+            EsFeature::At => EsVersion::ES2022,
+            EsFeature::AccessibleObjectPrototypeHasOwnProperty => EsVersion::ES2022,
+            EsFeature::ClassStaticBlock => EsVersion::ES2022,
+            EsFeature::ErrorCause => EsVersion::ES2022,
+            EsFeature::ArrayFindFromLast => EsVersion::ES2023,
+            EsFeature::HashbangGrammar => EsVersion::ES2023,
+            EsFeature::SymbolsAsWeakMapKeys => EsVersion::ES2023,
+            EsFeature::ChangeArrayByCopy => EsVersion::ES2023,
+            EsFeature::WellFormedUnicodeStrings => EsVersion::ES2024,
+            EsFeature::AtomicsWaitAsync => EsVersion::ES2024,
+            EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings => EsVersion::ES2024,
+            EsFeature::ArrayGrouping => EsVersion::ES2024,
+            EsFeature::ResizableAndGrowableArrayBuffers => EsVersion::ES2024,
+            EsFeature::PromiseWithResolvers => EsVersion::ES2024,
+            EsFeature::ArrayBufferTransfer => EsVersion::ES2024,
+            EsFeature::DuplicateNamedCaptureGroups => EsVersion::ES2025,
+            EsFeature::RegExpModifiers => EsVersion::ES2025,
+            EsFeature::NetSetMethods => EsVersion::ES2025,
+            EsFeature::IteratorHelpers => EsVersion::ES2025,
+            EsFeature::ImportAttributes => EsVersion::ES2025,
+            EsFeature::JsonModules => EsVersion::ES2025,
+            EsFeature::RegExpEscaping => EsVersion::ES2025,
+            EsFeature::Float16Array => EsVersion::ES2025,
+            EsFeature::PromiseTry => EsVersion::ES2025,
+            EsFeature::ExplicitResourceManagement => EsVersion::ES2026,
+            EsFeature::ExplicitResourceManagementBuiltIns => EsVersion::ES2026,
+            EsFeature::ArrayFromAsync => EsVersion::ES2026,
+            EsFeature::ErrorIsError => EsVersion::ES2026,
+            EsFeature::MathSumPrecise => EsVersion::ES2026,
+            EsFeature::Uint8ArrayBase64 => EsVersion::ES2026,
+            EsFeature::Decorators
+            | EsFeature::ImportDefer
+            | EsFeature::SourcePhaseImports
+            | EsFeature::Temporal
+            | EsFeature::ShadowRealm
+            | EsFeature::AsyncContext => EsVersion::ESNext,
         }
     }
 }
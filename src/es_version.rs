@@ -1,16 +1,92 @@
-use strum_macros::Display;
+use serde::Serialize;
+use strum_macros::{Display, EnumIter, EnumString};
 
-#[derive(Display, Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
+/// ECMAScript editions, named by the year they were published.
+/// Parsing also accepts the edition numbers (e.g. `ES7` for `ES2016`) and ignores case.
+#[derive(
+    Display,
+    EnumString,
+    EnumIter,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Eq,
+    Hash,
+)]
+#[strum(ascii_case_insensitive)]
 pub enum EsVersion {
-    ES7,
-    ES8,
-    ES9,
-    ES10,
-    ES11,
-    ES12,
-    ES13,
-    ES14,
-    ES15,
-    ES16,
+    ES3,
+    ES5,
+    #[strum(to_string = "ES2015", serialize = "ES6")]
+    ES2015,
+    #[strum(to_string = "ES2016", serialize = "ES7")]
+    ES2016,
+    #[strum(to_string = "ES2017", serialize = "ES8")]
+    ES2017,
+    #[strum(to_string = "ES2018", serialize = "ES9")]
+    ES2018,
+    #[strum(to_string = "ES2019", serialize = "ES10")]
+    ES2019,
+    #[strum(to_string = "ES2020", serialize = "ES11")]
+    ES2020,
+    #[strum(to_string = "ES2021", serialize = "ES12")]
+    ES2021,
+    #[strum(to_string = "ES2022", serialize = "ES13")]
+    ES2022,
+    #[strum(to_string = "ES2023", serialize = "ES14")]
+    ES2023,
+    #[strum(to_string = "ES2024", serialize = "ES15")]
+    ES2024,
+    #[strum(to_string = "ES2025", serialize = "ES16")]
+    ES2025,
+    #[strum(to_string = "ES2026", serialize = "ES17")]
+    ES2026,
     ESNext,
 }
+
+impl EsVersion {
+    /// Year the edition was published, `None` for `ESNext`
+    pub fn year(&self) -> Option<u16> {
+        match self {
+            EsVersion::ES3 => Some(1999),
+            EsVersion::ES5 => Some(2009),
+            EsVersion::ES2015 => Some(2015),
+            EsVersion::ES2016 => Some(2016),
+            EsVersion::ES2017 => Some(2017),
+            EsVersion::ES2018 => Some(2018),
+            EsVersion::ES2019 => Some(2019),
+            EsVersion::ES2020 => Some(2020),
+            EsVersion::ES2021 => Some(2021),
+            EsVersion::ES2022 => Some(2022),
+            EsVersion::ES2023 => Some(2023),
+            EsVersion::ES2024 => Some(2024),
+            EsVersion::ES2025 => Some(2025),
+            EsVersion::ES2026 => Some(2026),
+            EsVersion::ESNext => None,
+        }
+    }
+}
+
+impl From<swc_ecma_ast::EsVersion> for EsVersion {
+    fn from(value: swc_ecma_ast::EsVersion) -> Self {
+        match value {
+            swc_ecma_ast::EsVersion::Es3 => EsVersion::ES3,
+            swc_ecma_ast::EsVersion::Es5 => EsVersion::ES5,
+            swc_ecma_ast::EsVersion::Es2015 => EsVersion::ES2015,
+            swc_ecma_ast::EsVersion::Es2016 => EsVersion::ES2016,
+            swc_ecma_ast::EsVersion::Es2017 => EsVersion::ES2017,
+            swc_ecma_ast::EsVersion::Es2018 => EsVersion::ES2018,
+            swc_ecma_ast::EsVersion::Es2019 => EsVersion::ES2019,
+            swc_ecma_ast::EsVersion::Es2020 => EsVersion::ES2020,
+            swc_ecma_ast::EsVersion::Es2021 => EsVersion::ES2021,
+            swc_ecma_ast::EsVersion::Es2022 => EsVersion::ES2022,
+            swc_ecma_ast::EsVersion::Es2023 => EsVersion::ES2023,
+            swc_ecma_ast::EsVersion::Es2024 => EsVersion::ES2024,
+            swc_ecma_ast::EsVersion::EsNext => EsVersion::ESNext,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+use crate::{Error, Result};
+
+/// Controls which files [`find_js_files`] picks up
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    /// Extensions of files collected from directories, without the leading dot
+    pub extensions: Vec<String>,
+    /// If not empty, only files matching one of these globs are collected
+    pub include: Vec<String>,
+    /// Files matching one of these globs are skipped
+    pub exclude: Vec<String>,
+    /// Skip files ignored by `.gitignore`, `.ignore` and hidden files while walking directories
+    pub respect_ignore_files: bool,
+}
+
+impl Default for FileFilter {
+    fn default() -> Self {
+        FileFilter {
+            extensions: ["js", "mjs", "cjs", "jsx", "ts", "mts", "cts", "tsx"]
+                .map(String::from)
+                .to_vec(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            respect_ignore_files: true,
+        }
+    }
+}
+
+/// Expands files, directories and glob patterns into a sorted list of Javascript files.
+/// Files named explicitly are kept regardless of their extension, directories are walked
+/// recursively and only yield files with one of the filter's extensions.
+pub fn find_js_files(inputs: &[impl AsRef<str>], filter: &FileFilter) -> Result<Vec<PathBuf>> {
+    let include = build_glob_set(&filter.include)?;
+    let exclude = build_glob_set(&filter.exclude)?;
+    let selected = |path: &Path| {
+        (filter.include.is_empty() || include.is_match(path)) && !exclude.is_match(path)
+    };
+
+    let mut files = Vec::new();
+    for input in inputs {
+        let input = input.as_ref();
+        let path = Path::new(input);
+        if path.is_dir() {
+            walk_dir(path, filter, &selected, &mut files)?;
+        } else if path.exists() || !is_glob(input) {
+            // missing files are kept so that reading them reports the error
+            if selected(path) {
+                files.push(path.to_path_buf());
+            }
+        } else {
+            let entries = glob::glob(input).map_err(|e| Error::Pattern {
+                pattern: input.to_string(),
+                message: e.to_string(),
+            })?;
+            for entry in entries {
+                let path = entry.map_err(|e| Error::Io {
+                    path: e.path().display().to_string(),
+                    source: Arc::new(e.into()),
+                })?;
+                if path.is_dir() {
+                    walk_dir(&path, filter, &selected, &mut files)?;
+                } else if selected(&path) {
+                    files.push(path);
+                }
+            }
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn walk_dir(
+    dir: &Path,
+    filter: &FileFilter,
+    selected: &impl Fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let walker = WalkBuilder::new(dir)
+        .standard_filters(filter.respect_ignore_files)
+        // also honour .gitignore files outside of git repositories
+        .require_git(false)
+        .build();
+    for entry in walker {
+        let entry = entry.map_err(|e| Error::Io {
+            path: dir.display().to_string(),
+            source: Arc::new(std::io::Error::other(e.to_string())),
+        })?;
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let has_extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| filter.extensions.iter().any(|x| x == e));
+        if has_extension && selected(path) {
+            files.push(path.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|e| Error::Pattern {
+            pattern: pattern.clone(),
+            message: e.kind().to_string(),
+        })?);
+    }
+    builder.build().map_err(|e| Error::Pattern {
+        pattern: patterns.join(", "),
+        message: e.kind().to_string(),
+    })
+}
+
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
@@ -1,74 +1,381 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
-use std::rc::Rc;
+use std::path::Path;
+use std::sync::Arc;
 use swc_common::input::StringInput;
-use swc_common::{BytePos, FileName, SourceFile};
-use swc_ecma_ast::EsVersion;
+use swc_common::sync::Lrc;
+use swc_common::{FileName, Globals, Mark, SourceMap, Span, SyntaxContext, GLOBALS};
+use swc_ecma_ast::Program;
 use swc_ecma_parser::lexer::Lexer;
-use swc_ecma_parser::{EsSyntax, Parser, Syntax};
+use swc_ecma_parser::{EsSyntax, Parser, Syntax, TsSyntax};
+use swc_ecma_transforms_base::resolver;
 use swc_ecma_visit::VisitWith;
 use thiserror::Error;
 
-use crate::es_features::EsFeature;
+use crate::aliases::Aliases;
+use crate::polyfills::Polyfills;
 use crate::visitor::FeatureFinder;
 
-mod es_features;
-mod es_version;
+pub use crate::analyzer::{Analyzer, FileResult};
+pub use crate::browserslist::{read_browserslist_config, Browser, BrowserData};
+pub use crate::compat::{CompatTable, Engine, EngineVersion};
+pub use crate::diagnostic::Diagnostic;
+pub use crate::es_features::{EsFeature, FeatureKind};
+pub use crate::es_version::EsVersion;
+pub use crate::files::{find_js_files, FileFilter};
+pub use crate::location::Location;
+pub use crate::output::{render, OutputFormat};
+pub use crate::parse_goal::ParseGoal;
+pub use crate::report::{FeatureReport, Report};
+pub use crate::tsconfig::read_tsconfig_target;
+pub use strum::IntoEnumIterator;
+
+mod aliases;
+mod analyzer;
+mod browserslist;
+mod compat;
+mod diagnostic;
+pub mod es_features;
+pub mod es_version;
+mod files;
+mod location;
+mod output;
+mod parse_goal;
+mod polyfills;
+mod regex;
+mod report;
+mod tsconfig;
 mod visitor;
 
 #[derive(Error, Clone, Debug)]
 pub enum Error {
-    #[error("Parse error")]
-    ParserError,
+    /// The source contains syntax errors, fatal ones first
+    #[error("{}", parse_error_message(.diagnostics))]
+    ParserError { diagnostics: Vec<Diagnostic> },
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: Arc<std::io::Error>,
+    },
+    #[error("{path} is not valid UTF-8")]
+    Encoding { path: String },
+    #[error("No language features found")]
+    NoFeatures,
+    #[error("Invalid pattern {pattern}: {message}")]
+    Pattern { pattern: String, message: String },
+    #[error("Failed to start worker threads: {0}")]
+    ThreadPool(String),
+    #[error("Invalid configuration {path}: {message}")]
+    Config { path: String, message: String },
+    #[error("Invalid browserslist query {query}: {message}")]
+    Query { query: String, message: String },
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
 
-fn analyse(js: impl AsRef<str>) -> Result<HashSet<EsFeature>> {
-    let f = SourceFile::new(
-        Rc::from(FileName::Anon),
-        false,
-        Rc::from(FileName::Anon),
-        String::from(js.as_ref()),
-        BytePos(1),
-    );
+/// "Parse error at" the first diagnostic, which is the fatal one if there is any
+fn parse_error_message(diagnostics: &[Diagnostic]) -> String {
+    match diagnostics.first() {
+        Some(diagnostic) => format!("Parse error at {}", diagnostic),
+        None => "Parse error".to_string(),
+    }
+}
 
-    let lexer = Lexer::new(
+/// Result of parsing and visiting a single source
+struct Analysis {
+    cm: Lrc<SourceMap>,
+    finder: FeatureFinder,
+    /// The goal the source was parsed with, never `Auto`
+    goal: ParseGoal,
+    typescript: bool,
+    /// Errors the parser recovered from, like legacy octal literals in strict mode
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Analysis {
+    /// Resolves all required occurrences to locations sorted by their position in the source
+    fn locations(&self) -> HashMap<EsFeature, Vec<Location>> {
+        self.resolve(self.finder.get_occurrences())
+    }
+
+    /// Resolves the occurrences guarded by a feature test, like [`Analysis::locations`]
+    fn conditional_locations(&self) -> HashMap<EsFeature, Vec<Location>> {
+        self.resolve(self.finder.get_conditional_occurrences())
+    }
+
+    fn resolve(
+        &self,
+        occurrences: HashMap<EsFeature, Vec<Span>>,
+    ) -> HashMap<EsFeature, Vec<Location>> {
+        occurrences
+            .into_iter()
+            .map(|(feature, mut spans)| {
+                spans.sort();
+                let locations = spans
+                    .into_iter()
+                    .map(|span| Location::from_span(&self.cm, span))
+                    .collect();
+                (feature, locations)
+            })
+            .collect()
+    }
+}
+
+/// How sources are parsed, extensions of named files can override these settings
+#[derive(Debug, Clone, Copy, Default)]
+struct ParseOptions {
+    goal: ParseGoal,
+    /// Parse as JSX, always enabled for `.jsx` and `.tsx` files
+    jsx: bool,
+    /// Parse as TypeScript, always enabled for `.ts`, `.tsx`, `.mts` and `.cts` files
+    typescript: bool,
+    /// Parse and detect Stage 2.7/3 proposals
+    proposals: bool,
+}
+
+const TYPESCRIPT_EXTENSIONS: [&str; 4] = [".ts", ".tsx", ".mts", ".cts"];
+
+/// Parses and visits a source
+fn analyse(js: impl AsRef<str>, file_name: FileName, options: &ParseOptions) -> Result<Analysis> {
+    let mut options = *options;
+    let mut dts = false;
+    if let FileName::Custom(name) = &file_name {
+        options.goal = options.goal.for_file(name);
+        options.jsx |= name.ends_with(".jsx") || name.ends_with(".tsx");
+        options.typescript |= TYPESCRIPT_EXTENSIONS.iter().any(|e| name.ends_with(e));
+        dts = name.ends_with(".d.ts");
+    }
+    let syntax = if options.typescript {
+        Syntax::Typescript(TsSyntax {
+            tsx: options.jsx,
+            // TypeScript compiles decorators away, they are not the proposal
+            decorators: true,
+            dts,
+            ..Default::default()
+        })
+    } else {
         Syntax::Es(EsSyntax {
-            jsx: false,
+            jsx: options.jsx,
+            import_attributes: true,
+            explicit_resource_management: true,
+            decorators: options.proposals,
+            auto_accessors: options.proposals,
             ..Default::default()
-        }),
-        EsVersion::Es2022,
-        StringInput::from(&f),
+        })
+    };
+
+    let cm: Lrc<SourceMap> = Default::default();
+    let f = cm.new_source_file(Lrc::new(file_name), String::from(js.as_ref()));
+
+    let lexer = Lexer::new(
+        syntax,
+        swc_ecma_ast::EsVersion::Es2022,
+        StringInput::from(&*f),
         None,
     );
 
-    let program = Parser::new_from(lexer)
-        .parse_program()
-        .map_err(|_| Error::ParserError)?;
+    let mut parser = Parser::new_from(lexer);
+    let result = match options.goal {
+        ParseGoal::Script => parser.parse_script().map(Program::Script),
+        ParseGoal::Module => parser.parse_module().map(Program::Module),
+        ParseGoal::Auto => parser.parse_program(),
+    };
+    let recovered: Vec<Diagnostic> = parser
+        .take_errors()
+        .iter()
+        .map(|e| Diagnostic::from_parser_error(&cm, e, true))
+        .collect();
+    let program = result.map_err(|e| {
+        let mut diagnostics = vec![Diagnostic::from_parser_error(&cm, &e, false)];
+        diagnostics.extend(recovered.iter().cloned());
+        Error::ParserError { diagnostics }
+    })?;
+
+    // the resolver marks references to globals, so local bindings named like a built-in are ignored
+    let (finder, program) = GLOBALS.set(&Globals::new(), || {
+        let unresolved_mark = Mark::new();
+        let program = program.apply(resolver(unresolved_mark, Mark::new(), options.typescript));
+        let unresolved = SyntaxContext::empty().apply_mark(unresolved_mark);
+        let aliases = Aliases::collect(&program, unresolved);
+        let polyfills = Polyfills::collect(&program, &aliases);
+        let mut finder = FeatureFinder::new(
+            cm.clone(),
+            options.proposals,
+            options.typescript,
+            aliases,
+            polyfills,
+        );
+        program.visit_children_with(&mut finder);
+        (finder, program)
+    });
+
+    let goal = match program {
+        Program::Module(_) => ParseGoal::Module,
+        Program::Script(_) => ParseGoal::Script,
+    };
+    Ok(Analysis {
+        cm,
+        finder,
+        goal,
+        typescript: options.typescript,
+        diagnostics: recovered,
+    })
+}
 
-    let mut visitor = FeatureFinder::default();
-    program.visit_children_with(&mut visitor);
+/// Reads a Javascript file, failing with [`Error::Io`] or [`Error::Encoding`]
+pub fn read_js_file(path: impl AsRef<Path>) -> Result<String> {
+    read_text_file(path)
+}
 
-    Ok(visitor.get_result())
+/// Reads a UTF-8 file like a config or data file, failing with [`Error::Io`] or [`Error::Encoding`]
+pub(crate) fn read_text_file(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| Error::Io {
+        path: path.display().to_string(),
+        source: Arc::new(e),
+    })?;
+    String::from_utf8(bytes).map_err(|_| Error::Encoding {
+        path: path.display().to_string(),
+    })
 }
 
 /// Analyses the given Javascript and returns a set of recognized language features
 pub fn get_ecma_features(js: impl AsRef<str>) -> Result<HashSet<EsFeature>> {
-    analyse(js)
+    let analysis = analyse(js, FileName::Anon, &ParseOptions::default())?;
+    Ok(analysis.finder.get_result())
+}
+
+/// Analyses the given Javascript and returns every occurrence of each recognized language feature.
+/// Locations are reported against `file_name` and sorted by their position in the source.
+pub fn get_ecma_feature_locations(
+    js: impl AsRef<str>,
+    file_name: impl Into<String>,
+) -> Result<HashMap<EsFeature, Vec<Location>>> {
+    let analysis = analyse(
+        js,
+        FileName::Custom(file_name.into()),
+        &ParseOptions::default(),
+    )?;
+    Ok(analysis.locations())
+}
+
+/// Analyses the given Javascript and returns every occurrence of features that require a newer
+/// ECMAScript version than `target`. An empty result means the code runs on `target`.
+pub fn get_features_above(
+    js: impl AsRef<str>,
+    file_name: impl Into<String>,
+    target: EsVersion,
+) -> Result<HashMap<EsFeature, Vec<Location>>> {
+    let mut locations = get_ecma_feature_locations(js, file_name)?;
+    locations.retain(|feature, _| feature.version() > target);
+    Ok(locations)
+}
+
+/// Analyses the given Javascript and collects the result into a [`Report`].
+/// Parse errors are reported as the report's diagnostics instead of failing.
+pub fn get_report(
+    js: impl AsRef<str>,
+    file_name: impl Into<String>,
+    target: Option<EsVersion>,
+) -> Report {
+    Analyzer::new()
+        .with_target(target)
+        .analyse_source(js, file_name)
+}
+
+/// Analyses the given Javascript and returns the feature that requires the newest ECMAScript version.
+/// Fails with [`Error::NoFeatures`] if no feature is recognized.
+pub fn get_newest_feature(js: impl AsRef<str>) -> Result<EsFeature> {
+    let r = get_ecma_features(js)?;
+    r.into_iter()
+        .max_by_key(|f| (f.version(), *f))
+        .ok_or(Error::NoFeatures)
+}
+
+/// Analyses the given Javascript and returns the minimum ECMAScript version required.
+/// Code without any recognized feature only needs ES3.
+pub fn get_min_ecma_version(js: impl AsRef<str>) -> Result<EsVersion> {
+    let r = get_ecma_features(js)?;
+    Ok(r.iter()
+        .map(|f| f.version())
+        .max()
+        .unwrap_or(EsVersion::ES3))
+}
+
+/// Analyses the given Javascript and returns the minimum ECMAScript version required by its syntax,
+/// built-ins and lexical features separately, see [`EsFeature::kind`].
+/// Every kind is included, kinds without any recognized feature only need ES3.
+pub fn get_min_ecma_version_by_kind(
+    js: impl AsRef<str>,
+) -> Result<BTreeMap<FeatureKind, EsVersion>> {
+    let r = get_ecma_features(js)?;
+    Ok(FeatureKind::iter()
+        .map(|kind| {
+            let min_version = r
+                .iter()
+                .filter(|f| f.kind() == kind)
+                .map(|f| f.version())
+                .max()
+                .unwrap_or(EsVersion::ES3);
+            (kind, min_version)
+        })
+        .collect())
+}
+
+/// Like [`get_min_ecma_version`], but ignores built-ins the source polyfills itself by importing
+/// core-js or assigning to the built-in like `Object.fromEntries = …`
+pub fn get_min_ecma_version_after_polyfills(js: impl AsRef<str>) -> Result<EsVersion> {
+    let analysis = analyse(js, FileName::Anon, &ParseOptions::default())?;
+    let polyfilled = analysis.finder.get_polyfilled();
+    Ok(analysis
+        .finder
+        .get_result()
+        .into_iter()
+        .filter(|f| !polyfilled.contains(f))
+        .map(|f| f.version())
+        .max()
+        .unwrap_or(EsVersion::ES3))
+}
+
+/// Analyses the given Javascript and returns the minimum version of each engine that supports all
+/// recognized features according to the embedded [`CompatTable`], `None` for engines that lack one
+pub fn get_min_engine_versions(
+    js: impl AsRef<str>,
+) -> Result<BTreeMap<Engine, Option<EngineVersion>>> {
+    let r = get_ecma_features(js)?;
+    Ok(CompatTable::embedded().min_versions(r))
 }
 
-/// Analyses the given Javascript and returns the minimum ECMAScript version required
-pub fn get_min_ecma_version(js: impl AsRef<str>) -> Result<es_version::EsVersion> {
-    let r = analyse(js)?;
-    let max = r.iter().max();
-    Ok(max.ok_or(Error::ParserError)?.version())
+/// Analyses the given Javascript and returns the browsers of a browserslist query like
+/// `defaults, not IE 11` that lack each recognized feature, evaluated against the embedded data.
+/// Features every browser supports are left out.
+pub fn get_unsupported_browsers(
+    js: impl AsRef<str>,
+    query: &str,
+) -> Result<BTreeMap<EsFeature, Vec<Browser>>> {
+    let browsers = BrowserData::embedded().resolve(query)?;
+    let compat = CompatTable::embedded();
+    let r = get_ecma_features(js)?;
+    Ok(r.into_iter()
+        .map(|feature| {
+            let unsupported: Vec<Browser> = browsers
+                .iter()
+                .filter(|b| b.supports(feature, compat) == Some(false))
+                .cloned()
+                .collect();
+            (feature, unsupported)
+        })
+        .filter(|(_, unsupported)| !unsupported.is_empty())
+        .collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::collections::HashSet;
+    use std::ops::Deref;
+    use std::path::PathBuf;
 
     fn assert_features(js: &str, expected_features: &[EsFeature]) {
         let features_found = get_ecma_features(js).expect("Failed to parse JavaScript");
@@ -80,6 +387,232 @@ mod tests {
             .for_each(|expected_feature| assert!(features_found.contains(expected_feature)));
     }
 
+    /// Directory in the system's temp directory, removed on drop even if the test panics
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "js_version_checker_{}_{}",
+                name,
+                std::process::id()
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn assert_no_features(js: &str, unexpected_features: &[EsFeature]) {
+        let features_found = get_ecma_features(js).expect("Failed to parse JavaScript");
+        println!("Found: {:?}", features_found);
+        for feature in unexpected_features {
+            assert!(!features_found.contains(feature), "{} found", feature);
+        }
+    }
+
+    #[test]
+    fn test_arrow_functions() {
+        assert_features("const f = x => x * 2;", &[EsFeature::ArrowFunctions]);
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_features(
+            "class A extends B { constructor() { super(); } }",
+            &[EsFeature::Classes],
+        );
+    }
+
+    #[test]
+    fn test_static_methods_are_not_class_fields() {
+        let features = get_ecma_features("class A { static create() {} }").unwrap();
+        assert!(features.contains(&EsFeature::Classes));
+        assert!(!features.contains(&EsFeature::ClassFields));
+    }
+
+    #[test]
+    fn test_let_const() {
+        assert_features("let a = 1; const b = 2;", &[EsFeature::LetConst]);
+    }
+
+    #[test]
+    fn test_template_literals() {
+        assert_features(
+            "var s = `a${b}c`; var t = tag`x`;",
+            &[
+                EsFeature::TemplateLiterals,
+                EsFeature::TaggedTemplateLiterals,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_destructuring() {
+        assert_features(
+            "var { a } = o; var [b, c] = arr;",
+            &[EsFeature::Destructuring],
+        );
+    }
+
+    #[test]
+    fn test_default_and_rest_parameters() {
+        assert_features(
+            "function f(a = 1, ...rest) {}",
+            &[EsFeature::DefaultParameters, EsFeature::RestParameters],
+        );
+    }
+
+    #[test]
+    fn test_rest_parameters_are_not_rest_properties() {
+        let features = get_ecma_features("function f(...args) { g(...args); }").unwrap();
+        assert!(features.contains(&EsFeature::RestParameters));
+        assert!(features.contains(&EsFeature::SpreadOperator));
+        assert!(!features.contains(&EsFeature::RestSpreadProperties));
+    }
+
+    #[test]
+    fn test_shorthand_and_computed_properties() {
+        assert_features(
+            "var o = { a, m() {}, [k]: 1 };",
+            &[
+                EsFeature::ShorthandProperties,
+                EsFeature::ComputedProperties,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_computed_member_access_is_not_computed_property() {
+        let js = "var x = a[0]; for (var i = 0; i < n; i++) { o[k] = v; this[k](); }";
+        assert_no_features(js, &[EsFeature::ComputedProperties]);
+        assert_eq!(get_min_ecma_version(js).unwrap(), EsVersion::ES3);
+        assert_features(
+            "class A { [k]() {} } var { [k]: v } = o;",
+            &[EsFeature::ComputedProperties],
+        );
+    }
+
+    #[test]
+    fn test_for_of() {
+        assert_features("for (var x of xs) {}", &[EsFeature::ForOf]);
+    }
+
+    #[test]
+    fn test_generators() {
+        assert_features("function* gen() { yield 1; }", &[EsFeature::Generators]);
+    }
+
+    #[test]
+    fn test_new_target() {
+        assert_features(
+            "function F() { if (!new.target) {} }",
+            &[EsFeature::NewTarget],
+        );
+    }
+
+    #[test]
+    fn test_regexp_unicode_and_sticky_flags() {
+        assert_features(
+            "/a/u; new RegExp('a', 'y');",
+            &[EsFeature::RegExpUnicodeFlag, EsFeature::RegExpStickyFlag],
+        );
+    }
+
+    #[test]
+    fn test_keyed_collections() {
+        assert_features(
+            "var m = new Map(); var s = new WeakSet();",
+            &[EsFeature::KeyedCollections],
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert_features(
+            "var s = Symbol('a'); o[Symbol.iterator] = f;",
+            &[EsFeature::Symbols],
+        );
+    }
+
+    #[test]
+    fn test_proxy_and_reflect() {
+        assert_features(
+            "var p = new Proxy(t, { get: function (t, k) { return Reflect.get(t, k); } });",
+            &[EsFeature::Proxies, EsFeature::Reflect],
+        );
+    }
+
+    #[test]
+    fn test_promises() {
+        assert_features(
+            "new Promise(function (r) { r(); }); Promise.all(ps);",
+            &[EsFeature::Promises],
+        );
+    }
+
+    #[test]
+    fn test_typed_arrays() {
+        assert_features(
+            "var a = new Uint8Array(8); Float32Array.from(xs);",
+            &[EsFeature::TypedArrays],
+        );
+    }
+
+    #[test]
+    fn test_es2015_built_ins() {
+        assert_features(
+            "Object.assign({}, o); Array.from(xs); Number.isInteger(1); String.raw(s); Math.trunc(1.5);",
+            &[EsFeature::Es2015BuiltIns],
+        );
+        assert_eq!(
+            get_min_ecma_version("var m = new Map();").unwrap(),
+            EsVersion::ES2015
+        );
+    }
+
+    #[test]
+    fn test_array_prototype_includes() {
+        assert_features(
+            "[1, 2].includes(1); list.includes(x);",
+            &[EsFeature::ArrayPrototypeIncludes],
+        );
+        assert_no_features(
+            "'abc'.includes('a'); const s = `${a}b`; s.includes('b'); new String(a).includes('b');",
+            &[EsFeature::ArrayPrototypeIncludes],
+        );
+        // a string of unknown origin is taken for an array
+        assert_features(
+            "function f(s) { return s.includes('x'); }",
+            &[EsFeature::ArrayPrototypeIncludes],
+        );
+        assert_features(
+            "new Uint8Array(8).includes(0); const xs = new Array(3); xs.includes(1);",
+            &[EsFeature::ArrayPrototypeIncludes],
+        );
+        assert_no_features(
+            "const m = new Map(); m.at(0); m.finally(done); m.with(0, 1); [1].finally(done);",
+            &[
+                EsFeature::At,
+                EsFeature::PromisePrototypeFinally,
+                EsFeature::ChangeArrayByCopy,
+            ],
+        );
+    }
+
     #[test]
     fn test_exponentiation_operator() {
         assert_features("let x = 2 ** 3;", &[EsFeature::ExponentiationOperator]);
@@ -101,6 +634,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_padding() {
+        assert_features(
+            "s.padStart(4, '0'); s.padEnd(4);",
+            &[EsFeature::StringPadding],
+        );
+    }
+
+    #[test]
+    fn test_trailing_commas_in_function_parameter_lists_and_calls() {
+        for js in [
+            "function f(a, b,) {}",
+            "const f = (a, /* last */) => a;",
+            "class A { constructor(a,\n) {} m(a,) {} }",
+            "f(1, 2,);",
+            "new A(1, // comment\n);",
+            "a?.(1,);",
+        ] {
+            assert_features(
+                js,
+                &[EsFeature::TrailingCommasInFunctionParameterListsAndCalls],
+            );
+        }
+        let features = get_ecma_features("f(a, [1, 2,], {b,}); function g(a /* , */) {}").unwrap();
+        assert!(!features.contains(&EsFeature::TrailingCommasInFunctionParameterListsAndCalls));
+    }
+
     #[test]
     fn test_async_functions() {
         assert_features(
@@ -146,6 +706,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_regexp_named_capture_groups() {
+        assert_features(
+            "/(?<year>\\d{4})-\\k<year>/; new RegExp('(?<a>x)');",
+            &[EsFeature::RegExpNamedCaptureGroups],
+        );
+        let features = get_ecma_features("/(?<a>x)|(?<b>y)/;").unwrap();
+        assert!(!features.contains(&EsFeature::DuplicateNamedCaptureGroups));
+    }
+
+    #[test]
+    fn test_regexp_escaped_text_is_not_a_feature() {
+        let features =
+            get_ecma_features("/\\(?<=a\\)/; /[(?<=]/; /\\p{L}/; RegExp('[(?<a>)]');").unwrap();
+        assert!(features.is_empty(), "{:?}", features);
+    }
+
+    #[test]
+    fn test_regexp_features_from_constructor_calls() {
+        let features = get_ecma_features("RegExp('(?<!a)b\\\\p{L}', 'u');").unwrap();
+        assert_eq!(
+            features,
+            HashSet::from([
+                EsFeature::RegExpLookbehindAssertions,
+                EsFeature::RegExpUnicodePropertyEscapes,
+                EsFeature::RegExpUnicodeFlag,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_lifting_template_literal_restriction() {
+        assert_features(
+            "String.raw`\\unicode and \\u{55}`;",
+            &[EsFeature::LiftingTemplateLiteralRestriction],
+        );
+        let features = get_ecma_features("tag`\\n`;").unwrap();
+        assert!(!features.contains(&EsFeature::LiftingTemplateLiteralRestriction));
+    }
+
+    #[test]
+    fn test_promise_prototype_finally() {
+        assert_features(
+            "fetch(url).finally(() => done());",
+            &[EsFeature::PromisePrototypeFinally],
+        );
+    }
+
+    #[test]
+    fn test_asynchronous_iteration() {
+        for js in [
+            "async function f() { for await (const x of xs) {} }",
+            "async function* g() {}",
+            "const o = { async *[Symbol.asyncIterator]() {} };",
+        ] {
+            assert_features(js, &[EsFeature::AsynchronousIteration]);
+        }
+        let features = get_ecma_features("const it = obj[Symbol.asyncIterator]();").unwrap();
+        assert!(features.contains(&EsFeature::SymbolAsyncIterator));
+        assert!(!features.contains(&EsFeature::AsynchronousIteration));
+    }
+
     #[test]
     fn test_optional_catch_binding() {
         assert_features(
@@ -154,6 +776,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_json_superset() {
+        assert_features("const s = 'a\u{2028}b';", &[EsFeature::JSONSuperset]);
+        let features = get_ecma_features("const s = '\\u2028';").unwrap();
+        assert!(!features.contains(&EsFeature::JSONSuperset));
+    }
+
+    #[test]
+    fn test_symbol_prototype_description() {
+        assert_features(
+            "Symbol('a').description; mySymbol.description;",
+            &[EsFeature::SymbolPrototypeDescription],
+        );
+        assert_no_features(
+            "item.description; const Symbol = s => ({ description: s }); Symbol('a').description;",
+            &[EsFeature::SymbolPrototypeDescription],
+        );
+    }
+
+    #[test]
+    fn test_string_prototype_trim_start_trim_end() {
+        assert_features(
+            "s.trimStart(); s.trimEnd();",
+            &[EsFeature::StringPrototypeTrimStartTrimEnd],
+        );
+    }
+
+    #[test]
+    fn test_array_prototype_flat_flat_map() {
+        assert_features(
+            "[[1]].flat(); xs.flatMap(x => [x, x]);",
+            &[EsFeature::ArrayPrototypeFlatFlatMap],
+        );
+    }
+
     #[test]
     fn test_object_from_entries() {
         assert_features(
@@ -162,9 +819,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_prototype_match_all() {
+        assert_features(
+            "for (const m of s.matchAll(/a/g)) {}",
+            &[EsFeature::StringPrototypeMatchAll],
+        );
+    }
+
+    #[test]
+    fn test_dynamic_import() {
+        let report = get_report("import('./a.js').then(m => m.run());", "a.js", None);
+        assert!(report
+            .features
+            .iter()
+            .any(|f| f.feature == EsFeature::Import));
+        assert!(!report.requires_module);
+    }
+
     #[test]
     fn test_bigint() {
-        assert_features("const bigInt = 123n;", &[EsFeature::BigInt]);
+        assert_features("const bigInt = 123n;", &[EsFeature::BigIntLiterals]);
+        assert_features("const bigInt = BigInt(123);", &[EsFeature::BigInt]);
     }
 
     #[test]
@@ -201,6 +877,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_import_meta() {
+        assert_features(
+            "const url = new URL('a.txt', import.meta.url);",
+            &[EsFeature::ImportMeta],
+        );
+    }
+
+    #[test]
+    fn test_string_prototype_replace_all() {
+        assert_features(
+            "s.replaceAll('a', 'b');",
+            &[EsFeature::StringPrototypeReplaceAll],
+        );
+    }
+
+    #[test]
+    fn test_weak_refs() {
+        assert_features(
+            "const r = new WeakRef(o); const f = new FinalizationRegistry(cb);",
+            &[EsFeature::WeakRefs],
+        );
+    }
+
     #[test]
     fn test_promise_any() {
         assert_features("Promise.any(promises);", &[EsFeature::PromiseAny]);
@@ -254,42 +954,1396 @@ mod tests {
     }
 
     #[test]
-    fn test_class_static_block() {
+    fn test_at() {
+        assert_features("const last = xs.at(-1);", &[EsFeature::At]);
+    }
+
+    #[test]
+    fn test_error_cause() {
         assert_features(
-            "class MyClass { static { console.log('Static block!'); } }",
-            &[EsFeature::ClassStaticBlock],
+            "throw new Error('failed', { cause: e }); TypeError('x', { cause });",
+            &[EsFeature::ErrorCause],
         );
+        let features = get_ecma_features("new Error('failed', { code: 1 });").unwrap();
+        assert!(!features.contains(&EsFeature::ErrorCause));
     }
 
     #[test]
-    fn test_atomics_wait_async() {
+    fn test_array_find_from_last() {
         assert_features(
-            "Atomics.waitAsync(sharedArrayBuffer, 0, 0);",
-            &[EsFeature::AtomicsWaitAsync],
+            "xs.findLast(x => x); xs.findLastIndex(x => x);",
+            &[EsFeature::ArrayFindFromLast],
         );
     }
 
     #[test]
-    fn test_regexp_v_flag_with_set_notation_and_properties_of_strings() {
+    fn test_hashbang_grammar() {
         assert_features(
-            "/[\\p{L}&&[^a]]/v;",
-            &[EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings],
+            "#!/usr/bin/env node\nconsole.log(1);",
+            &[EsFeature::HashbangGrammar],
         );
     }
 
     #[test]
-    fn test_array_grouping() {
+    fn test_symbols_as_weak_map_keys() {
+        for js in [
+            "const weakCache = new WeakMap(); weakCache.set(Symbol('key'), 1);",
+            "new WeakRef(Symbol.iterator);",
+            "new WeakMap([[Symbol(), 1]]);",
+        ] {
+            assert_features(js, &[EsFeature::SymbolsAsWeakMapKeys]);
+        }
         assert_features(
-            "const grouped = [1, 2, 3].groupBy(x => x % 2);",
-            &[EsFeature::ArrayGrouping],
+            "const cache = new WeakMap(); cache.set(Symbol(), 1);",
+            &[EsFeature::SymbolsAsWeakMapKeys],
+        );
+        assert_features(
+            "const key = Symbol(); new FinalizationRegistry(cleanup).register(key, 1);",
+            &[EsFeature::SymbolsAsWeakMapKeys],
         );
+        for js in [
+            "new Map().set(Symbol(), 1); const weakish = new Map(); weakish.set(Symbol(), 1);",
+            // looking up a symbol does not throw in older engines
+            "const cache = new WeakMap(); cache.get(Symbol.iterator); cache.has(Symbol());",
+            // neither receiver nor key is known
+            "weakCache.set(mySymbol, 1); registry.register(Symbol(), 1);",
+            "const cache = new WeakMap(); cache.set(sym, 1);",
+        ] {
+            assert_no_features(js, &[EsFeature::SymbolsAsWeakMapKeys]);
+        }
     }
 
     #[test]
-    fn test_promise_with_resolvers() {
+    fn test_change_array_by_copy() {
         assert_features(
-            "const { promise, resolve, reject } = Promise.withResolvers();",
-            &[EsFeature::PromiseWithResolvers],
+            "xs.toSorted(); xs.toReversed(); xs.toSpliced(0, 1); xs.with(0, 1);",
+            &[EsFeature::ChangeArrayByCopy],
+        );
+        assert_features("xs.with(-1, 'last');", &[EsFeature::ChangeArrayByCopy]);
+        assert_no_features(
+            "knex.with('cte', q); Temporal.Now.plainDateISO().with({ day: 1 }); dayjs().with(x);",
+            &[EsFeature::ChangeArrayByCopy],
         );
     }
+
+    #[test]
+    fn test_class_static_block() {
+        assert_features(
+            "class MyClass { static { console.log('Static block!'); } }",
+            &[EsFeature::ClassStaticBlock],
+        );
+    }
+
+    #[test]
+    fn test_atomics_wait_async() {
+        assert_features(
+            "Atomics.waitAsync(sharedArrayBuffer, 0, 0);",
+            &[EsFeature::AtomicsWaitAsync],
+        );
+    }
+
+    #[test]
+    fn test_regexp_v_flag_with_set_notation_and_properties_of_strings() {
+        assert_features(
+            "/[\\p{L}&&[^a]]/v;",
+            &[EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings],
+        );
+    }
+
+    #[test]
+    fn test_duplicate_named_capture_groups() {
+        assert_features(
+            "/(?<year>\\d{4})-\\d\\d|\\d\\d-(?<year>\\d{4})/;",
+            &[EsFeature::DuplicateNamedCaptureGroups],
+        );
+    }
+
+    #[test]
+    fn test_regexp_modifiers() {
+        assert_features("/(?i:a)b(?-m:^c)/;", &[EsFeature::RegExpModifiers]);
+    }
+
+    #[test]
+    fn test_array_grouping() {
+        assert_features(
+            "const grouped = [1, 2, 3].groupBy(x => x % 2);",
+            &[EsFeature::ArrayGrouping],
+        );
+    }
+
+    #[test]
+    fn test_promise_with_resolvers() {
+        assert_features(
+            "const { promise, resolve, reject } = Promise.withResolvers();",
+            &[EsFeature::PromiseWithResolvers],
+        );
+    }
+
+    #[test]
+    fn test_well_formed_unicode_strings() {
+        assert_features(
+            "s.isWellFormed() || s.toWellFormed();",
+            &[EsFeature::WellFormedUnicodeStrings],
+        );
+    }
+
+    #[test]
+    fn test_resizable_and_growable_array_buffers() {
+        assert_features(
+            "const buffer = new ArrayBuffer(8, { maxByteLength: 16 }); buffer.resize(12);",
+            &[EsFeature::ResizableAndGrowableArrayBuffers],
+        );
+        let features = get_ecma_features("chart.resize();").unwrap();
+        assert!(!features.contains(&EsFeature::ResizableAndGrowableArrayBuffers));
+    }
+
+    #[test]
+    fn test_array_buffer_transfer() {
+        assert_features(
+            "const moved = buffer.transfer(); buffer.detached; b.transferToFixedLength();",
+            &[EsFeature::ArrayBufferTransfer],
+        );
+    }
+
+    #[test]
+    fn test_net_set_methods() {
+        assert_features(
+            "a.union(b); a.isSubsetOf(b); a.symmetricDifference(b);",
+            &[EsFeature::NetSetMethods],
+        );
+        assert_features("new Set(a).union(b);", &[EsFeature::NetSetMethods]);
+        let features = get_ecma_features("const s = new Set(xs); s.intersection(b);").unwrap();
+        assert!(features.contains(&EsFeature::NetSetMethods));
+        assert_no_features(
+            "query.union(other); range.intersection(b); _.difference(a, b); new Map().union(m);",
+            &[EsFeature::NetSetMethods],
+        );
+    }
+
+    #[test]
+    fn test_iterator_helpers() {
+        for js in [
+            "map.values().filter(x => x).toArray();",
+            "Iterator.from(it).take(2);",
+            "Iterator.prototype.map;",
+        ] {
+            assert_features(js, &[EsFeature::IteratorHelpers]);
+        }
+        assert_no_features(
+            "Object.entries(o).map(([k, v]) => k); const Iterator = { from: Array.from }; Iterator.from(it).map(f);",
+            &[EsFeature::IteratorHelpers],
+        );
+    }
+
+    #[test]
+    fn test_import_attributes_and_json_modules() {
+        assert_features(
+            "import data from './data.json' with { type: 'json' };",
+            &[EsFeature::ImportAttributes, EsFeature::JsonModules],
+        );
+        assert_features(
+            "const m = await import('./a.json', { with: { type: 'json' } });",
+            &[EsFeature::ImportAttributes, EsFeature::JsonModules],
+        );
+        let features = get_ecma_features("export * from './a.css' with { type: 'css' };").unwrap();
+        assert!(features.contains(&EsFeature::ImportAttributes));
+        assert!(!features.contains(&EsFeature::JsonModules));
+    }
+
+    #[test]
+    fn test_regexp_escaping() {
+        assert_features(
+            "new RegExp(RegExp.escape(input));",
+            &[EsFeature::RegExpEscaping],
+        );
+    }
+
+    #[test]
+    fn test_float16_array() {
+        assert_features(
+            "new Float16Array(4); Math.f16round(1.5); view.getFloat16(0);",
+            &[EsFeature::Float16Array],
+        );
+    }
+
+    #[test]
+    fn test_promise_try() {
+        assert_features("Promise.try(() => run());", &[EsFeature::PromiseTry]);
+    }
+
+    #[test]
+    fn test_explicit_resource_management() {
+        for js in [
+            "{ using file = open(); }",
+            "async function f() { await using conn = connect(); }",
+        ] {
+            assert_features(js, &[EsFeature::ExplicitResourceManagement]);
+        }
+        for js in [
+            "const stack = new DisposableStack();",
+            "class R { [Symbol.dispose]() {} }",
+        ] {
+            let features = get_ecma_features(js).unwrap();
+            assert!(features.contains(&EsFeature::ExplicitResourceManagementBuiltIns));
+            assert!(!features.contains(&EsFeature::ExplicitResourceManagement));
+        }
+    }
+
+    #[test]
+    fn test_array_from_async() {
+        assert_features(
+            "const xs = await Array.fromAsync(stream);",
+            &[EsFeature::ArrayFromAsync],
+        );
+    }
+
+    #[test]
+    fn test_error_is_error() {
+        assert_features("Error.isError(e);", &[EsFeature::ErrorIsError]);
+    }
+
+    #[test]
+    fn test_math_sum_precise() {
+        assert_features("Math.sumPrecise([0.1, 0.2]);", &[EsFeature::MathSumPrecise]);
+    }
+
+    #[test]
+    fn test_uint8_array_base64() {
+        assert_features(
+            "Uint8Array.fromBase64(s).toHex(); bytes.toBase64();",
+            &[EsFeature::Uint8ArrayBase64],
+        );
+    }
+
+    #[test]
+    fn test_es2025_and_es2026_editions() {
+        assert_eq!(
+            get_min_ecma_version("Promise.try(f);").unwrap(),
+            EsVersion::ES2025
+        );
+        assert_eq!(
+            get_min_ecma_version("Error.isError(e);").unwrap(),
+            EsVersion::ES2026
+        );
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        assert_features(
+            "'use strict'; function f() { \"use strict\"; }",
+            &[EsFeature::StrictMode],
+        );
+    }
+
+    #[test]
+    fn test_property_accessors() {
+        assert_features(
+            "var o = { get a() { return 1; }, set a(v) {} };",
+            &[EsFeature::PropertyAccessors],
+        );
+    }
+
+    #[test]
+    fn test_es5_built_ins() {
+        assert_features(
+            "Object.keys(o); Array.isArray(a); JSON.stringify(o);",
+            &[EsFeature::Es5BuiltIns],
+        );
+    }
+
+    #[test]
+    fn test_min_version_baseline() {
+        assert_eq!(
+            get_min_ecma_version("var x = 1; function f(a) { return a + x; }").unwrap(),
+            EsVersion::ES3
+        );
+        assert_eq!(
+            get_min_ecma_version("var keys = Object.keys({ a: 1 });").unwrap(),
+            EsVersion::ES5
+        );
+        assert_eq!(
+            get_min_ecma_version("var f = () => Object.keys({});").unwrap(),
+            EsVersion::ES2015
+        );
+        assert_eq!(
+            get_min_ecma_version("var x = 2 ** 3; Object.keys({});").unwrap(),
+            EsVersion::ES2016
+        );
+    }
+
+    #[test]
+    fn test_parser_error_diagnostics() {
+        let Err(Error::ParserError { diagnostics }) =
+            get_ecma_feature_locations("var a = 1;\nvar b = ;", "broken.js")
+        else {
+            panic!("expected a parser error");
+        };
+        let diagnostic = diagnostics.first().expect("a diagnostic");
+        assert!(!diagnostic.recoverable);
+        assert_eq!(diagnostic.location.to_string(), "broken.js:2:9");
+        assert_eq!(diagnostic.start, 19);
+        assert_eq!(diagnostic.code_frame, "2 | var b = ;\n  |         ^");
+
+        let empty = Error::ParserError {
+            diagnostics: Vec::new(),
+        };
+        assert_eq!(empty.to_string(), "Parse error");
+        let diagnostic = Diagnostic::from_error("a.js", &empty);
+        assert_eq!(diagnostic.to_string(), "a.js:1:1: Parse error\n");
+    }
+
+    #[test]
+    fn test_recoverable_parser_errors() {
+        let js = "'use strict'; var x = 010; const f = () => x;";
+        let features = get_ecma_features(js).unwrap();
+        assert!(features.contains(&EsFeature::ArrowFunctions));
+
+        let report = Analyzer::new().analyse_source(js, "octal.js");
+        assert_eq!(report.min_version, Some(EsVersion::ES2015));
+        assert_eq!(report.diagnostics.len(), 1);
+        assert!(report.diagnostics[0].recoverable);
+        assert_eq!(report.diagnostics[0].location.to_string(), "octal.js:1:23");
+        assert!(report.passed());
+        assert!(render(&[report], OutputFormat::Text).starts_with("warning: octal.js:1:23: "));
+    }
+
+    #[test]
+    fn test_newest_feature() {
+        assert_eq!(
+            get_newest_feature("let a = x?.y; let b = 2 ** 3;").unwrap(),
+            EsFeature::OptionalChaining
+        );
+        assert!(matches!(
+            get_newest_feature("var a = 1;"),
+            Err(Error::NoFeatures)
+        ));
+    }
+
+    #[test]
+    fn test_features_above_target() {
+        let js = "var a = x?.y;\nvar b = 2 ** 3;\nvar c = d ?? e;";
+        let above = get_features_above(js, "test.js", EsVersion::ES2019).unwrap();
+        let mut features: Vec<_> = above.keys().copied().collect();
+        features.sort();
+        assert_eq!(
+            features,
+            vec![
+                EsFeature::OptionalChaining,
+                EsFeature::NullishCoalescingOperator
+            ]
+        );
+        assert_eq!(above[&EsFeature::NullishCoalescingOperator][0].line, 3);
+
+        assert!(get_features_above(js, "test.js", EsVersion::ES2020)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_report() {
+        let report = get_report(
+            "var a = x?.y;\nlet b = 1;",
+            "test.js",
+            Some(EsVersion::ES2019),
+        );
+        assert_eq!(report.min_version, Some(EsVersion::ES2020));
+        assert_eq!(report.newest_feature(), Some(EsFeature::OptionalChaining));
+        assert!(!report.passed());
+        let features: Vec<_> = report.features.iter().map(|f| f.feature).collect();
+        assert_eq!(
+            features,
+            vec![EsFeature::LetConst, EsFeature::OptionalChaining]
+        );
+
+        let report = get_report("var b = ;", "broken.js", None);
+        assert_eq!(report.min_version, None);
+        assert_eq!(report.diagnostics.len(), 1);
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let reports = vec![get_report(
+            "var a = x?.y;",
+            "test.js",
+            Some(EsVersion::ES2019),
+        )];
+
+        assert_eq!(
+            render(&reports, OutputFormat::Text),
+            "test.js: requires at least ES2020 (OptionalChaining)\n  test.js:1:9: OptionalChaining requires ES2020, target is ES2019\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&reports, OutputFormat::Json)).unwrap();
+        assert_eq!(json["min_version"], "ES2020");
+        assert_eq!(
+            json["files"][0]["features"][0]["feature"],
+            "OptionalChaining"
+        );
+        assert_eq!(json["files"][0]["features"][0]["locations"][0]["column"], 9);
+
+        let ndjson = render(&reports, OutputFormat::Ndjson);
+        assert_eq!(ndjson.lines().count(), 1);
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&render(&reports, OutputFormat::Sarif)).unwrap();
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "OptionalChaining");
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            1
+        );
+    }
+
+    #[test]
+    fn test_find_js_files() {
+        let dir = TempDir::new("files");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::create_dir_all(dir.join("vendor")).unwrap();
+        std::fs::write(dir.join(".gitignore"), "vendor\n").unwrap();
+        for file in ["a.js", "b.txt", "sub/c.mjs", "sub/d.cjs", "vendor/e.js"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        let names = |filter: &FileFilter| -> Vec<String> {
+            find_js_files(&[dir.to_str().unwrap()], filter)
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(&*dir).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(&FileFilter::default()),
+            vec!["a.js", "sub/c.mjs", "sub/d.cjs"]
+        );
+        assert_eq!(
+            names(&FileFilter {
+                exclude: vec!["**/sub/*.cjs".into()],
+                respect_ignore_files: false,
+                ..Default::default()
+            }),
+            vec!["a.js", "sub/c.mjs", "vendor/e.js"]
+        );
+
+        let pattern = format!("{}/sub/*", dir.display());
+        let files = find_js_files(&[pattern], &FileFilter::default()).unwrap();
+        assert_eq!(files.len(), 2);
+    }
+
+    #[test]
+    fn test_analyzer_is_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Analyzer>();
+        assert_send_sync::<FileResult>();
+    }
+
+    #[test]
+    fn test_analyse_files_in_parallel() {
+        let dir = TempDir::new("batch");
+        let sources = ["let a = 1;", "a?.b;", "var c = ;", "var d = 2 ** 2;"];
+        let mut paths: Vec<_> = sources
+            .iter()
+            .enumerate()
+            .map(|(i, js)| {
+                let path = dir.join(format!("{}.js", i));
+                std::fs::write(&path, js).unwrap();
+                path
+            })
+            .collect();
+        paths.push(dir.join("missing.js"));
+
+        let analyzer = Analyzer::new()
+            .with_target(Some(EsVersion::ES2016))
+            .with_threads(Some(2));
+        let results = analyzer.analyse_files(&paths).unwrap();
+        assert_eq!(
+            results.iter().map(|r| &r.path).collect::<Vec<_>>(),
+            paths.iter().collect::<Vec<_>>()
+        );
+
+        let versions: Vec<_> = results
+            .iter()
+            .map(|r| r.report.as_ref().ok().and_then(|r| r.min_version))
+            .collect();
+        assert_eq!(
+            versions,
+            vec![
+                Some(EsVersion::ES2015),
+                Some(EsVersion::ES2020),
+                None,
+                Some(EsVersion::ES2016),
+                None
+            ]
+        );
+        assert!(!results[1].report.as_ref().unwrap().passed());
+        assert!(matches!(results[4].report, Err(Error::Io { .. })));
+    }
+
+    #[test]
+    fn test_parse_goal() {
+        let analyzer = Analyzer::new();
+        let report = analyzer.analyse_source("var a = 1;", "a.js");
+        assert_eq!(report.goal, Some(ParseGoal::Script));
+        assert!(!report.requires_module);
+
+        let report = analyzer.analyse_source("export var a = 1;", "a.js");
+        assert_eq!(report.goal, Some(ParseGoal::Module));
+        assert!(report.requires_module);
+        assert!(report
+            .features
+            .iter()
+            .any(|f| f.feature == EsFeature::Modules));
+
+        let report = analyzer.analyse_source("var a = 1;", "a.mjs");
+        assert_eq!(report.goal, Some(ParseGoal::Module));
+        assert!(!report.requires_module);
+
+        let report = analyzer.analyse_source("export var a = 1;", "a.cjs");
+        assert!(!report.diagnostics.is_empty());
+
+        let report = Analyzer::new()
+            .with_goal(ParseGoal::Script)
+            .analyse_source("import x from 'x';", "a.mjs");
+        assert!(!report.diagnostics.is_empty());
+
+        for js in ["await f();", "console.log(import.meta.url);"] {
+            let report = analyzer.analyse_source(js, "a.js");
+            assert!(report.requires_module, "{}", js);
+        }
+    }
+
+    #[test]
+    fn test_jsx() {
+        let js =
+            "const App = () => <div className={a ?? 'b'}>{items.map(i => <Item {...i} />)}</div>;";
+        assert!(!Analyzer::new()
+            .analyse_source(js, "app.js")
+            .diagnostics
+            .is_empty());
+
+        for report in [
+            Analyzer::new().with_jsx(true).analyse_source(js, "app.js"),
+            Analyzer::new().analyse_source(js, "app.jsx"),
+        ] {
+            assert!(report.diagnostics.is_empty());
+            assert_eq!(report.min_version, Some(EsVersion::ES2020));
+            let features: Vec<_> = report.features.iter().map(|f| f.feature).collect();
+            assert_eq!(
+                features,
+                vec![
+                    EsFeature::ArrowFunctions,
+                    EsFeature::LetConst,
+                    EsFeature::NullishCoalescingOperator
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_typescript() {
+        let ts = r#"
+            import type { Options } from './options';
+            interface Point { x: number; y?: number }
+            type Pair = [Point, Point];
+            declare const VERSION: string;
+            declare function log(message: string): void;
+            declare module 'untyped' { export const x: number; }
+            enum Color { Red, Green = 2 }
+            namespace Util { export var id = 1; }
+            class Shape {
+                declare kind: string;
+                constructor(private name: string, readonly size = 1) {}
+                area(this: Shape, factor?: number): number;
+                area(): number { return (this.name as any).length; }
+            }
+            function measure<T extends object>(value: T): T | undefined { return value!; }
+        "#;
+        let report = Analyzer::new().analyse_source(ts, "shapes.ts");
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+        let features: Vec<_> = report.features.iter().map(|f| f.feature).collect();
+        assert_eq!(
+            features,
+            vec![EsFeature::Classes, EsFeature::DefaultParameters]
+        );
+        assert!(!report.requires_module);
+
+        let report = Analyzer::new()
+            .with_tsconfig_target(Some(EsVersion::ES2017))
+            .analyse_source("const a = b?.c;", "a.ts");
+        assert_eq!(
+            report.warnings,
+            vec!["requires ES2020 but tsconfig.json targets ES2017"]
+        );
+        let report = Analyzer::new()
+            .with_tsconfig_target(Some(EsVersion::ES2017))
+            .analyse_source("const a = b?.c;", "a.js");
+        assert!(report.warnings.is_empty());
+
+        let report = Analyzer::new().analyse_source("const e = <div>{a as string}</div>;", "a.tsx");
+        assert!(report.diagnostics.is_empty());
+
+        // decorators and fields with only a type are erased
+        let report = Analyzer::new().analyse_source(
+            "@Component({ selector: 'app' })\nclass A { @Input() name: string; x: number; constructor(@Inject(T) t) {} }",
+            "a.component.ts",
+        );
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+        let features: Vec<_> = report.features.iter().map(|f| f.feature).collect();
+        assert_eq!(features, vec![EsFeature::Classes]);
+        let report = Analyzer::new().analyse_source("class A { x: number = 1; }", "a.ts");
+        assert!(report
+            .features
+            .iter()
+            .any(|f| f.feature == EsFeature::ClassFields));
+    }
+
+    #[test]
+    fn test_read_tsconfig_target() {
+        let dir = TempDir::new("ts");
+        let path = dir.join("tsconfig.json");
+        std::fs::write(
+            &path,
+            "{\n  // comment\n  \"compilerOptions\": {\n    \"target\": \"es2019\", /* \"target\": \"es5\" */\n    \"outDir\": \"dist//out\",\n  },\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_tsconfig_target(&path).unwrap(),
+            Some(EsVersion::ES2019)
+        );
+
+        std::fs::write(&path, "{}").unwrap();
+        assert_eq!(read_tsconfig_target(&path).unwrap(), None);
+
+        std::fs::write(&path, "{\"compilerOptions\": {\"target\": \"es1\"}}").unwrap();
+        assert!(matches!(
+            read_tsconfig_target(&path),
+            Err(Error::Config { .. })
+        ));
+    }
+
+    #[test]
+    fn test_es_version_names() {
+        assert_eq!("ES2020".parse::<EsVersion>().unwrap(), EsVersion::ES2020);
+        assert_eq!("es11".parse::<EsVersion>().unwrap(), EsVersion::ES2020);
+        assert_eq!("ES6".parse::<EsVersion>().unwrap(), EsVersion::ES2015);
+        assert_eq!(EsVersion::ES2020.to_string(), "ES2020");
+        assert_eq!(
+            EsVersion::from(swc_ecma_ast::EsVersion::Es2022),
+            EsVersion::ES2022
+        );
+        assert!(EsVersion::iter().is_sorted());
+        assert_eq!(
+            "OptionalChaining".parse::<EsFeature>().unwrap(),
+            EsFeature::OptionalChaining
+        );
+        assert!(EsFeature::iter()
+            .filter(|f| !f.is_proposal())
+            .all(|f| f.version() < EsVersion::ESNext));
+        assert!(EsFeature::iter()
+            .filter(EsFeature::is_proposal)
+            .all(|f| f.edition().is_none()));
+    }
+
+    #[test]
+    fn test_shadowed_globals_are_ignored() {
+        for js in [
+            "var Promise = require('bluebird'); Promise.any([a, b]);",
+            "function wait(Atomics) { return Atomics.waitAsync(buf, 0, 0); }",
+            "import { Map } from 'immutable'; new Map();",
+            "try {} catch (Reflect) { Reflect.x; }",
+            "{ let Temporal = x; class Proxy {} new Proxy(); }",
+        ] {
+            let features = get_ecma_features(js).unwrap();
+            for feature in [
+                EsFeature::PromiseAny,
+                EsFeature::AtomicsWaitAsync,
+                EsFeature::KeyedCollections,
+                EsFeature::Reflect,
+                EsFeature::Proxies,
+            ] {
+                assert!(!features.contains(&feature), "{} in {}", feature, js);
+            }
+        }
+    }
+
+    #[test]
+    fn test_globals_are_resolved_across_scopes() {
+        assert_features(
+            "function f(Promise) {} function g() { return Promise.any(xs); }",
+            &[EsFeature::PromiseAny],
+        );
+        assert_features(
+            "const o = { Atomics: 1 }; Atomics.waitAsync(buf, 0, 0);",
+            &[EsFeature::AtomicsWaitAsync],
+        );
+    }
+
+    #[test]
+    fn test_aliased_built_ins() {
+        assert_features(
+            "const { fromEntries } = Object; fromEntries(pairs);",
+            &[EsFeature::ObjectFromEntries],
+        );
+        assert_features("const P = Promise; P.any(xs);", &[EsFeature::PromiseAny]);
+        assert_features(
+            "const { any: first } = window.Promise; first(xs);",
+            &[EsFeature::PromiseAny],
+        );
+        assert_features(
+            "const values = Object.values; values(o);",
+            &[EsFeature::ObjectValuesEntries],
+        );
+        assert_features("Object['values'](o);", &[EsFeature::ObjectValuesEntries]);
+        assert_features("(0, Object.entries)(o);", &[EsFeature::ObjectValuesEntries]);
+        assert_features("globalThis.Promise.any(xs);", &[EsFeature::PromiseAny]);
+        assert_features(
+            "const A = Atomics; A.waitAsync(buf, 0, 0);",
+            &[EsFeature::AtomicsWaitAsync],
+        );
+    }
+
+    #[test]
+    fn test_reassigned_aliases_are_ignored() {
+        for js in [
+            "let P = Promise; P = Bluebird; P.any(xs);",
+            "let { any } = Promise; any = polyfill; any(xs);",
+            "const P = require('bluebird'); P.any(xs);",
+            "let P = Promise; P ||= Bluebird; P.any(xs);",
+            "let P = Promise; [P] = [Bluebird]; P.any(xs);",
+            "let { any } = Promise; ({ any } = Bluebird); any(xs);",
+            "let { any } = Promise; ({ first: any = shim } = lib); any(xs);",
+            "let any = Promise.any; [, ...any] = fns; any(xs);",
+        ] {
+            let features = get_ecma_features(js).unwrap();
+            assert!(!features.contains(&EsFeature::PromiseAny), "{}", js);
+        }
+    }
+
+    #[test]
+    fn test_guarded_built_ins_are_conditional() {
+        for js in [
+            "if (typeof Promise.any === 'function') { Promise.any(xs); } else { shim(xs); }",
+            "var first = 'any' in Promise ? Promise.any(xs) : shim(xs);",
+            "typeof window.Promise !== 'undefined' && Promise.any(xs);",
+            "if (!Promise.any) { shim(); } else { Promise.any(xs); }",
+            "if (typeof Promise.any === 'undefined' || !Object.hasOwn) {} else { Promise.any(xs); }",
+            "var any = !('any' in Promise) ? shim : Promise.any(xs);",
+            "try { new Function('return async () => {}'); Promise.any(xs); } catch (e) {}",
+        ] {
+            assert!(
+                !get_ecma_features(js)
+                    .unwrap()
+                    .contains(&EsFeature::PromiseAny),
+                "{}",
+                js
+            );
+            let report = get_report(js, "a.js", Some(EsVersion::ES2020));
+            let conditional: Vec<EsFeature> = report.conditional().map(|f| f.feature).collect();
+            assert_eq!(conditional, [EsFeature::PromiseAny], "{}", js);
+            assert_eq!(report.min_version, Some(EsVersion::ES3), "{}", js);
+            assert!(report.passed(), "{}", js);
+        }
+
+        let report = get_report(
+            "if (Array.prototype.at) { xs.at(-1); } else { xs[xs.length - 1]; }",
+            "a.js",
+            None,
+        );
+        let conditional: Vec<EsFeature> = report.conditional().map(|f| f.feature).collect();
+        assert_eq!(conditional, [EsFeature::At]);
+        assert_eq!(report.min_version, Some(EsVersion::ES3));
+
+        let report = get_report("var ownKeys = Reflect.ownKeys || shim;", "a.js", None);
+        let conditional: Vec<EsFeature> = report.conditional().map(|f| f.feature).collect();
+        assert_eq!(conditional, [EsFeature::Reflect]);
+        assert_eq!(report.min_version, Some(EsVersion::ES3));
+    }
+
+    #[test]
+    fn test_guards_do_not_hide_required_usages() {
+        // environment checks are not feature tests
+        assert_features(
+            "if (typeof window !== 'undefined') { Object.fromEntries(pairs); }",
+            &[EsFeature::ObjectFromEntries],
+        );
+        // a guard only covers the branch where the built-in exists
+        for js in [
+            "if (typeof Promise.any === 'function') {} else { Promise.any(xs); }",
+            "if (!Promise.any) { Promise.any(xs); }",
+            "var first = 'any' in Promise ? shim(xs) : Promise.any(xs);",
+            "Promise.any || Promise.any(xs);",
+        ] {
+            assert_features(js, &[EsFeature::PromiseAny]);
+        }
+        // a guard only covers the built-ins it tests
+        assert_features(
+            "if (typeof Symbol === 'function') { Object.fromEntries(p); Promise.any(xs); }",
+            &[EsFeature::ObjectFromEntries, EsFeature::PromiseAny],
+        );
+        assert_features(
+            "var v = 'any' in Promise ? Object.hasOwn(o, 'a') : 1;",
+            &[EsFeature::AccessibleObjectPrototypeHasOwnProperty],
+        );
+        // bare conditions on application globals are no feature tests
+        assert_features(
+            "if (MyApp.debug) { Promise.any(xs); }",
+            &[EsFeature::PromiseAny],
+        );
+        assert_features(
+            "if (Foo) {} else { Object.hasOwn(o, 'a'); }",
+            &[EsFeature::AccessibleObjectPrototypeHasOwnProperty],
+        );
+        // a BigInt literal fails to parse in older engines, no matter the guard
+        for js in [
+            "var n = typeof BigInt === 'function' ? 10n : 10;",
+            "try { new Function('return 1n'); var n = 10n; } catch (e) {}",
+            "require('core-js/stable/bigint'); var n = 10n;",
+        ] {
+            let report = get_report(js, "a.js", None);
+            assert_eq!(report.min_version, Some(EsVersion::ES2020), "{}", js);
+            assert_eq!(report.conditional().count(), 0, "{}", js);
+        }
+        // syntax fails to parse in older engines, no matter the guard
+        assert_features(
+            "if (typeof Symbol === 'function') { const f = () => 2 ** 10; }",
+            &[EsFeature::ArrowFunctions, EsFeature::ExponentiationOperator],
+        );
+
+        let js = "if (typeof Promise.any === 'function') { Promise.any(xs); }\nPromise.any(ys);";
+        let report = get_report(js, "a.js", None);
+        let any = report
+            .features
+            .iter()
+            .find(|f| f.feature == EsFeature::PromiseAny)
+            .unwrap();
+        assert!(!any.conditional);
+        assert_eq!(any.locations.len(), 2);
+        assert_eq!(report.min_version, Some(EsVersion::ES2021));
+
+        let js = "if (typeof Promise.any === 'function') { Promise.any(xs); }";
+        let report = Analyzer::new()
+            .with_count_conditional(true)
+            .analyse_source(js, "a.js");
+        assert_eq!(report.min_version, Some(EsVersion::ES2021));
+        assert_eq!(report.conditional().count(), 0);
+    }
+
+    #[test]
+    fn test_polyfill_imports() {
+        for js in [
+            "import 'core-js/stable'; Object.fromEntries(p); Promise.any(xs);",
+            "require('core-js'); Object.fromEntries(p); Promise.any(xs);",
+            "import 'core-js/es/object/from-entries'; import 'core-js/modules/es.promise.any'; Object.fromEntries(p); Promise.any(xs);",
+            "require('core-js/actual/object'); require('core-js/stable/promise'); Object.fromEntries(p); Promise.any(xs);",
+        ] {
+            assert_eq!(get_min_ecma_version(js).unwrap(), EsVersion::ES2021, "{}", js);
+            assert!(
+                get_min_ecma_version_after_polyfills(js).unwrap() <= EsVersion::ES2015,
+                "{}",
+                js
+            );
+        }
+
+        let js = "import 'core-js/stable/object/from-entries';\nimport 'regenerator-runtime/runtime';\nObject.fromEntries(p);\n[1].flat();";
+        let report = get_report(js, "a.js", Some(EsVersion::ES2018));
+        assert_eq!(
+            report.polyfills,
+            [
+                "core-js/stable/object/from-entries",
+                "regenerator-runtime/runtime"
+            ]
+        );
+        let polyfilled: Vec<EsFeature> = report.polyfilled().map(|f| f.feature).collect();
+        assert_eq!(polyfilled, [EsFeature::ObjectFromEntries]);
+        assert_eq!(report.min_version, Some(EsVersion::ES2019));
+        assert_eq!(report.min_version_after_polyfills, Some(EsVersion::ES2019));
+        // polyfilled built-ins stay above the target, but do not fail it
+        let above: Vec<EsFeature> = report
+            .features
+            .iter()
+            .filter(|f| f.above_target)
+            .map(|f| f.feature)
+            .collect();
+        assert_eq!(
+            above,
+            [
+                EsFeature::ObjectFromEntries,
+                EsFeature::ArrayPrototypeFlatFlatMap
+            ]
+        );
+        let text = render(&[report], OutputFormat::Text);
+        assert!(text.contains("a.js:3:1: ObjectFromEntries (ES2019, polyfilled)"));
+        assert!(text.contains("a.js:4:1: ArrayPrototypeFlatFlatMap requires ES2019"));
+
+        // a module polyfills its own built-ins, not others of the same feature
+        let js = "import 'core-js/stable/set'; new Set(a); new Map();";
+        let report = get_report(js, "a.js", Some(EsVersion::ES5));
+        assert_eq!(report.polyfilled().count(), 0);
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_polyfill_assignments() {
+        for js in [
+            "Object.fromEntries = function (entries) {}; Object.fromEntries(p);",
+            "if (!Object.fromEntries) { Object.fromEntries = shim; } Object.fromEntries(p);",
+            "Object.defineProperty(Object, 'fromEntries', { value: shim }); Object.fromEntries(p);",
+            "Array.prototype.flat = shim; Object.defineProperty(String.prototype, 'padStart', {}); [].flat(); s.padStart(2);",
+        ] {
+            // `Object.defineProperty` itself is ES5
+            assert!(
+                get_min_ecma_version_after_polyfills(js).unwrap() <= EsVersion::ES5,
+                "{}",
+                js
+            );
+            assert!(get_min_ecma_version(js).unwrap() > EsVersion::ES2015, "{}", js);
+        }
+        // an assigned built-in covers the members of its own feature, nothing else
+        for (js, version) in [
+            (
+                "window.Promise = Bluebird; new Promise(f); Promise.all(xs);",
+                EsVersion::ES3,
+            ),
+            (
+                "Object.values = shim; Object.fromEntries(p);",
+                EsVersion::ES2019,
+            ),
+            (
+                "Math.sign = shim; Math.sign(x); Object.assign(a, b);",
+                EsVersion::ES2015,
+            ),
+            (
+                "window.Promise = Bluebird; Promise.any(xs);",
+                EsVersion::ES2021,
+            ),
+        ] {
+            assert_eq!(
+                get_min_ecma_version_after_polyfills(js).unwrap(),
+                version,
+                "{}",
+                js
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_version_by_kind() {
+        let js = "const a = x?.y;\nPromise.any(xs);\n/(?<=a)b/;";
+        let by_kind = get_min_ecma_version_by_kind(js).unwrap();
+        assert_eq!(by_kind[&FeatureKind::Syntax], EsVersion::ES2020);
+        assert_eq!(by_kind[&FeatureKind::BuiltIn], EsVersion::ES2021);
+        assert_eq!(by_kind[&FeatureKind::Lexical], EsVersion::ES2018);
+
+        let report = get_report(js, "a.js", None);
+        assert_eq!(report.min_version_by_kind, by_kind);
+        assert!(render(&[report], OutputFormat::Text)
+            .contains("requires at least ES2021 (PromiseAny); syntax ES2020, built-ins ES2021, lexical ES2018"));
+
+        let by_kind = get_min_ecma_version_by_kind("var n = 1_000;").unwrap();
+        assert_eq!(by_kind[&FeatureKind::Syntax], EsVersion::ES3);
+        assert_eq!(by_kind[&FeatureKind::BuiltIn], EsVersion::ES3);
+        assert_eq!(by_kind[&FeatureKind::Lexical], EsVersion::ES2021);
+
+        let by_kind = get_min_ecma_version_by_kind("var n = 10n;").unwrap();
+        assert_eq!(by_kind[&FeatureKind::BuiltIn], EsVersion::ES3);
+        assert_eq!(by_kind[&FeatureKind::Lexical], EsVersion::ES2020);
+
+        let by_kind = get_min_ecma_version_by_kind("var it = xs[Symbol.asyncIterator]();").unwrap();
+        assert_eq!(by_kind[&FeatureKind::Syntax], EsVersion::ES3);
+        assert_eq!(by_kind[&FeatureKind::BuiltIn], EsVersion::ES2018);
+        // unlike `for await`, the symbol can be feature tested
+        let js = "var it = typeof Symbol.asyncIterator === 'symbol' ? xs[Symbol.asyncIterator]() : null;";
+        assert!(get_report(js, "a.js", None)
+            .conditional()
+            .any(|f| f.feature == EsFeature::SymbolAsyncIterator));
+    }
+
+    #[test]
+    fn test_feature_kinds() {
+        assert_eq!(EsFeature::OptionalChaining.kind(), FeatureKind::Syntax);
+        assert_eq!(EsFeature::ClassFields.kind(), FeatureKind::Syntax);
+        assert_eq!(EsFeature::PromiseAny.kind(), FeatureKind::BuiltIn);
+        assert_eq!(EsFeature::ObjectValuesEntries.kind(), FeatureKind::BuiltIn);
+        assert_eq!(EsFeature::RegExpMatchIndices.kind(), FeatureKind::Lexical);
+    }
+
+    #[test]
+    fn test_proposals_are_opt_in() {
+        let js = "@sealed class A { accessor x = 1; }\nconst d = Temporal.Now.plainDateISO();\nnew ShadowRealm();\nAsyncContext.Variable;";
+        let report = get_report(js, "a.js", None);
+        assert!(
+            !report.diagnostics.is_empty(),
+            "decorators need --proposals"
+        );
+
+        let report = Analyzer::new()
+            .with_proposals(true)
+            .with_target(Some(EsVersion::ES2022))
+            .analyse_source(js, "a.js");
+        let proposals: Vec<EsFeature> = report.proposals().map(|f| f.feature).collect();
+        assert_eq!(
+            proposals,
+            [
+                EsFeature::Decorators,
+                EsFeature::Temporal,
+                EsFeature::ShadowRealm,
+                EsFeature::AsyncContext
+            ]
+        );
+        assert_eq!(report.min_version, Some(EsVersion::ES2022));
+        assert_eq!(report.newest_feature(), Some(EsFeature::ClassFields));
+        assert!(report.proposals().all(|f| f.above_target));
+        assert!(!report.passed());
+        assert!(render(&[report], OutputFormat::Text)
+            .contains("a.js:2:11: Temporal is a proposal, not in any edition, target is ES2022"));
+    }
+
+    #[test]
+    fn test_import_phase_proposals() {
+        let js = "import defer * as ns from './a.js';\nimport source wasm from './b.wasm';\nimport.source('./c.wasm');";
+        let report = Analyzer::new()
+            .with_proposals(true)
+            .analyse_source(js, "a.mjs");
+        let proposals: Vec<EsFeature> = report.proposals().map(|f| f.feature).collect();
+        assert_eq!(
+            proposals,
+            [EsFeature::ImportDefer, EsFeature::SourcePhaseImports]
+        );
+        assert_eq!(report.min_version, Some(EsVersion::ES2020));
+
+        let report = get_report(js, "a.mjs", None);
+        assert_eq!(report.proposals().count(), 0);
+    }
+
+    #[test]
+    fn test_feature_locations() {
+        let js = "let a = x?.y;\nlet b = 2 ** 3;\n  let c = u?.v;";
+        let locations =
+            get_ecma_feature_locations(js, "test.js").expect("Failed to parse JavaScript");
+
+        let optional_chaining = &locations[&EsFeature::OptionalChaining];
+        assert_eq!(optional_chaining.len(), 2);
+        assert_eq!(optional_chaining[0].to_string(), "test.js:1:9");
+        assert_eq!(optional_chaining[1].to_string(), "test.js:3:11");
+
+        let exponentiation = &locations[&EsFeature::ExponentiationOperator];
+        assert_eq!(exponentiation.len(), 1);
+        assert_eq!((exponentiation[0].line, exponentiation[0].column), (2, 9));
+    }
+
+    #[test]
+    fn test_compat_table() {
+        let table = CompatTable::embedded();
+        // every cell is filled in, even if only with "unsupported"
+        let json: serde_json::Value = serde_json::from_str(&table.to_json()).unwrap();
+        for feature in EsFeature::iter() {
+            for engine in Engine::iter() {
+                assert!(json[feature.to_string()].get(engine.id()).is_some());
+            }
+        }
+        assert_eq!(
+            CompatTable::from_json(&table.to_json()).unwrap().to_json(),
+            table.to_json()
+        );
+        assert!(table
+            .first_version(EsFeature::OptionalChaining, Engine::Chrome)
+            .is_some_and(|v| *v == "80".parse().unwrap()));
+        assert!("14.0".parse::<EngineVersion>().unwrap() == "14".parse().unwrap());
+        assert!("13.1".parse::<EngineVersion>().unwrap() > "13".parse().unwrap());
+        assert!("2021-03-27".parse::<EngineVersion>().unwrap() > "2020-11-08".parse().unwrap());
+        assert_eq!("safari_ios".parse::<Engine>().unwrap(), Engine::IosSafari);
+        assert_eq!(Engine::QuickJs.to_string(), "QuickJS");
+    }
+
+    #[test]
+    fn test_min_engine_versions() {
+        let versions = get_min_engine_versions("let a = x?.y ?? 1;").unwrap();
+        assert_eq!(versions.len(), Engine::iter().count());
+        assert_eq!(versions[&Engine::Chrome], Some("80".parse().unwrap()));
+        assert_eq!(versions[&Engine::Firefox], Some("74".parse().unwrap()));
+        assert_eq!(versions[&Engine::Node], Some("14".parse().unwrap()));
+
+        let versions = get_min_engine_versions("async function f() {}").unwrap();
+        assert_eq!(versions[&Engine::Hermes], None);
+
+        let versions = get_min_engine_versions("var a = 1;").unwrap();
+        assert!(versions.values().all(Option::is_some));
+
+        let report = Analyzer::new()
+            .with_compat_table(Some(Arc::new(CompatTable::embedded().clone())))
+            .analyse_source(
+                "if (typeof Promise.any === 'function') Promise.any(xs);",
+                "a.js",
+            );
+        assert_eq!(report.engines[&Engine::Chrome], Some("1".parse().unwrap()));
+        assert!(render(&[report], OutputFormat::Text).contains("  engines: Chrome 1, Edge 12,"));
+        assert!(get_report("a?.b", "a.js", None).engines.is_empty());
+    }
+
+    #[test]
+    fn test_compat_table_from_bcd() {
+        let dir = TempDir::new("bcd");
+        let path = dir.join("data.json");
+        std::fs::write(
+            &path,
+            r#"{"javascript": {
+                "operators": {"optional_chaining": {"__compat": {"support": {
+                    "chrome": {"version_added": "91"},
+                    "firefox": [{"version_added": "70", "flags": [{}]}, {"version_added": "≤72"}],
+                    "safari": {"version_added": "preview"},
+                    "nodejs": {"version_added": null}
+                }}}},
+                "statements": {"for...of": {"__compat": {"support": {
+                    "chrome": {"version_added": "40"}
+                }}}}
+            }}"#,
+        )
+        .unwrap();
+        let table = CompatTable::from_bcd_file(&path).unwrap();
+        let version = |feature, engine| table.first_version(feature, engine).cloned();
+        assert_eq!(
+            version(EsFeature::OptionalChaining, Engine::Chrome),
+            Some("91".parse().unwrap())
+        );
+        assert_eq!(
+            version(EsFeature::OptionalChaining, Engine::Firefox),
+            Some("72".parse().unwrap())
+        );
+        assert_eq!(version(EsFeature::OptionalChaining, Engine::Safari), None);
+        // unknown in the file, the embedded version is kept
+        assert_eq!(
+            version(EsFeature::OptionalChaining, Engine::Node),
+            Some("14".parse().unwrap())
+        );
+        assert_eq!(
+            version(EsFeature::ForOf, Engine::Chrome),
+            Some("40".parse().unwrap())
+        );
+        assert_eq!(
+            version(EsFeature::PromiseAny, Engine::Chrome),
+            CompatTable::embedded()
+                .first_version(EsFeature::PromiseAny, Engine::Chrome)
+                .cloned()
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            CompatTable::from_bcd_file(&path),
+            Err(Error::Config { .. })
+        ));
+    }
+
+    #[test]
+    fn test_browserslist_queries() {
+        let data = BrowserData::embedded();
+        let resolve = |query: &str| -> Vec<String> {
+            data.resolve(query)
+                .unwrap()
+                .iter()
+                .map(|b| b.to_string())
+                .collect()
+        };
+        assert_eq!(
+            resolve("last 2 Chrome versions"),
+            ["chrome 137", "chrome 136"]
+        );
+        assert_eq!(resolve("chrome > 135 and chrome < 137"), ["chrome 136"]);
+        assert_eq!(resolve("ios 15.2"), ["ios_saf 15.2-15.3"]);
+        assert_eq!(resolve("safari 14-14.1"), ["safari 14.1", "safari 14"]);
+        assert_eq!(resolve("node 18"), ["node 18.20.8"]);
+        assert_eq!(resolve("Firefox ESR"), ["firefox 128", "firefox 115"]);
+        assert_eq!(
+            resolve("last 1 chrome versions or last 1 firefox versions"),
+            ["chrome 137", "firefox 139"]
+        );
+        assert!(resolve("> 5%").contains(&"and_chr 137".to_string()));
+
+        let defaults = resolve("defaults");
+        assert!(defaults.contains(&"firefox 115".to_string()));
+        assert!(!defaults.contains(&"ie 11".to_string()));
+        assert_eq!(resolve("not ie 11"), defaults);
+        assert!(!resolve("defaults, not chrome 137").contains(&"chrome 137".to_string()));
+
+        assert!(matches!(data.resolve("foo 1"), Err(Error::Query { .. })));
+        assert!(matches!(
+            data.resolve("chrome 999"),
+            Err(Error::Query { .. })
+        ));
+        assert!(matches!(
+            data.resolve("supports es6-module"),
+            Err(Error::Query { .. })
+        ));
+    }
+
+    #[test]
+    fn test_maintained_node_versions() {
+        let data = BrowserData::embedded();
+        let maintained = |date: &str| -> Vec<String> {
+            data.resolve_at("maintained node versions", date)
+                .unwrap()
+                .iter()
+                .map(|b| b.to_string())
+                .collect()
+        };
+        assert_eq!(
+            maintained("2025-01-01"),
+            [
+                "node 24.1.0",
+                "node 23.11.1",
+                "node 22.16.0",
+                "node 20.19.2",
+                "node 18.20.8"
+            ]
+        );
+        assert_eq!(maintained("2026-10-18"), ["node 24.1.0", "node 22.16.0"]);
+        // once every end of life has passed, the snapshot selects no Node versions
+        assert!(maintained("2099-01-01").is_empty());
+        assert!(matches!(
+            data.resolve_at("maintained node versions", "tomorrow"),
+            Err(Error::Query { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_browsers() {
+        let unsupported =
+            get_unsupported_browsers("let a = x?.y;", "safari 13, chrome 80, ie 11").unwrap();
+        let browsers: Vec<String> = unsupported[&EsFeature::OptionalChaining]
+            .iter()
+            .map(|b| b.to_string())
+            .collect();
+        assert_eq!(browsers, ["ie 11", "safari 13"]);
+        assert!(unsupported[&EsFeature::LetConst]
+            .iter()
+            .all(|b| b.name == "ie"));
+        assert!(
+            get_unsupported_browsers("'use strict'; Object.keys(a);", "ie 10")
+                .unwrap()
+                .is_empty()
+        );
+        let without_data: Vec<String> = BrowserData::embedded()
+            .resolve("op_mini all, ie 6, android 4.4, chrome 80")
+            .unwrap()
+            .iter()
+            .filter(|b| !b.has_compat_data())
+            .map(|b| b.to_string())
+            .collect();
+        assert_eq!(without_data, ["op_mini all"]);
+
+        let browsers = BrowserData::embedded().resolve("chrome 80").unwrap();
+        let analyzer = Analyzer::new().with_browsers(browsers);
+        let report = analyzer.analyse_source("a ??= b;\nlet c = d?.e;", "a.js");
+        assert!(!report.passed());
+        let above: Vec<EsFeature> = report
+            .features
+            .iter()
+            .filter(|f| f.above_target)
+            .map(|f| f.feature)
+            .collect();
+        assert_eq!(above, [EsFeature::LogicalAssignmentOperators]);
+        let text = render(&[report], OutputFormat::Text);
+        assert!(text.contains("a.js:1:1: LogicalAssignmentOperators is unsupported by chrome 80"));
+        assert!(!text.contains("OptionalChaining"));
+
+        // polyfilled and guarded built-ins do not fail
+        let report = analyzer.analyse_source(
+            "require('core-js/stable/promise/any');\nPromise.any(xs);\nif (typeof Object.hasOwn === 'function') Object.hasOwn(a, 'b');",
+            "a.js",
+        );
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_read_browserslist_config() {
+        let dir = TempDir::new("bl");
+        let sub = dir.join("src").join("app");
+        std::fs::create_dir_all(&sub).unwrap();
+
+        let rc = dir.join(".browserslistrc");
+        std::fs::write(
+            &rc,
+            "# comment\n> 1%\nlast 2 versions # trailing\n\n[development staging]\nlast 1 chrome version\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_browserslist_config(&sub, "production").unwrap(),
+            Some("> 1%, last 2 versions".to_string())
+        );
+        assert_eq!(
+            read_browserslist_config(&rc, "staging").unwrap(),
+            Some("last 1 chrome version".to_string())
+        );
+        std::fs::remove_file(&rc).unwrap();
+
+        let package = dir.join("package.json");
+        std::fs::write(&package, "{\"name\": \"app\"}").unwrap();
+        assert_eq!(
+            read_browserslist_config(&package, "production").unwrap(),
+            None
+        );
+        std::fs::write(
+            &package,
+            "{\"browserslist\": {\"production\": [\"defaults\", \"not ie 11\"], \"development\": \"chrome 120\"}}",
+        )
+        .unwrap();
+        assert_eq!(
+            read_browserslist_config(&sub, "production").unwrap(),
+            Some("defaults, not ie 11".to_string())
+        );
+        assert_eq!(
+            read_browserslist_config(&sub, "development").unwrap(),
+            Some("chrome 120".to_string())
+        );
+        assert_eq!(
+            read_browserslist_config(&sub, "test").unwrap(),
+            Some("defaults".to_string())
+        );
+
+        std::fs::write(
+            &package,
+            "{\"browserslist\": \"extends @company/browserslist-config\"}",
+        )
+        .unwrap();
+        assert!(matches!(
+            read_browserslist_config(&package, "production"),
+            Err(Error::Config { .. })
+        ));
+    }
+
+    #[test]
+    fn test_browser_data_from_caniuse() {
+        let dir = TempDir::new("ciu");
+        let path = dir.join("data.json");
+        std::fs::write(
+            &path,
+            r#"{"agents": {"chrome": {"version_list": [
+                {"version": "139", "global_usage": 2.5, "release_date": 1754352000},
+                {"version": "140", "global_usage": 9.1, "release_date": 1756771200},
+                {"version": "141", "global_usage": 0, "release_date": null}
+            ]}}}"#,
+        )
+        .unwrap();
+        let data = BrowserData::from_caniuse_file(&path).unwrap();
+        let names = |query: &str| -> Vec<String> {
+            data.resolve(query)
+                .unwrap()
+                .iter()
+                .map(|b| b.to_string())
+                .collect()
+        };
+        assert_eq!(names("last 1 chrome versions"), ["chrome 140"]);
+        assert!(names("> 5%").contains(&"chrome 140".to_string()));
+        assert!(!names("> 5%").contains(&"chrome 139".to_string()));
+        assert_eq!(names("unreleased chrome versions"), ["chrome 141"]);
+        // agents that are not in the file keep their embedded versions
+        assert_eq!(names("last 1 firefox versions"), ["firefox 139"]);
+
+        std::fs::write(&path, "{}").unwrap();
+        assert!(matches!(
+            BrowserData::from_caniuse_file(&path),
+            Err(Error::Config { .. })
+        ));
+    }
 }
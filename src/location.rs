@@ -0,0 +1,32 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+use swc_common::{SourceMap, Span};
+
+/// Position of a single feature occurrence in the analysed source
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Location {
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+}
+
+impl Location {
+    /// Resolves a span against the source map it was parsed with
+    pub(crate) fn from_span(cm: &SourceMap, span: Span) -> Self {
+        let loc = cm.lookup_char_pos(span.lo);
+        Location {
+            file: loc.file.name.to_string(),
+            line: loc.line,
+            column: loc.col.0 + 1,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
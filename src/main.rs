@@ -1,37 +1,337 @@
 extern crate js_version_checker_lib;
 
 use std::error::Error;
-use std::fs::File;
-use std::io::Read;
+use std::sync::Arc;
 
-use clap::{command, Arg, ColorChoice};
-use log::error;
+use clap::{command, Arg, ArgAction, ColorChoice};
+use js_version_checker_lib::{
+    read_browserslist_config, read_tsconfig_target, Analyzer, BrowserData, CompatTable, EsVersion,
+    FileFilter, OutputFormat, ParseGoal,
+};
+use log::{error, warn};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    env_logger::init();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
     let match_result = command!()
         .version("1")
-        .about("Checks the ECMAScript version of a Javascript file")
-        .arg(Arg::new("path").help("path to JavaScript file"))
+        .about("Checks the ECMAScript version of Javascript files")
+        .arg(
+            Arg::new("paths")
+                .help("JavaScript files, directories or glob patterns")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("target")
+                .long("target")
+                .value_name("VERSION")
+                .help("fail if a file needs a newer ECMAScript version than this, e.g. ES2019")
+                .value_parser(|s: &str| s.parse::<EsVersion>()),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("output format: text, json, ndjson or sarif")
+                .default_value("text")
+                .value_parser(|s: &str| s.parse::<OutputFormat>()),
+        )
+        .arg(
+            Arg::new("browsers")
+                .long("browsers")
+                .value_name("QUERY")
+                .help("fail if a feature is unsupported by a browser of this browserslist query, e.g. \"defaults, not IE 11\""),
+        )
+        .arg(
+            Arg::new("browserslist")
+                .long("browserslist")
+                .value_name("PATH")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value(".")
+                .conflicts_with("browsers")
+                .help("like --browsers with the query of a .browserslistrc or package.json, searched upwards from --browserslist=PATH [default: .]"),
+        )
+        .arg(
+            Arg::new("usage-data")
+                .long("usage-data")
+                .value_name("PATH")
+                .help("evaluate browserslist queries against the data.json of caniuse instead of the embedded data"),
+        )
+        .arg(
+            Arg::new("goal")
+                .long("goal")
+                .value_name("GOAL")
+                .help(
+                    "parse files as script, module or auto (by .mjs/.cjs extension, then content)",
+                )
+                .default_value("auto")
+                .value_parser(|s: &str| s.parse::<ParseGoal>()),
+        )
+        .arg(
+            Arg::new("jsx")
+                .long("jsx")
+                .help("parse all files as JSX, .jsx files are always parsed as JSX")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("ts")
+                .long("ts")
+                .help("parse all files as TypeScript, .ts/.tsx/.mts/.cts files are always parsed as TypeScript")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tsconfig")
+                .long("tsconfig")
+                .value_name("PATH")
+                .help("warn about TypeScript files that need a newer version than the tsconfig.json target"),
+        )
+        .arg(
+            Arg::new("proposals")
+                .long("proposals")
+                .help("detect Stage 2.7/3 proposals like decorators or Temporal, they fail every --target")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("count-conditional")
+                .long("count-conditional")
+                .help("count built-ins guarded by a feature test like `typeof Promise.any === 'function'` as required")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("engines")
+                .long("engines")
+                .help("report the minimum versions of Chrome, Firefox, Safari, Node and other engines")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("compat-data")
+                .long("compat-data")
+                .value_name("PATH")
+                .help("update the engine versions from the data.json of MDN browser-compat-data, implies --engines"),
+        )
+        .arg(
+            Arg::new("dump-compat-data")
+                .long("dump-compat-data")
+                .help("print the engine compatibility table as JSON and exit")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("include")
+                .long("include")
+                .value_name("GLOB")
+                .help("only check files matching this glob, can be repeated")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .value_name("GLOB")
+                .help("skip files matching this glob, can be repeated")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("ext")
+                .long("ext")
+                .value_name("EXTENSION")
+                .help("extensions of files checked in directories [default: js, mjs, cjs, jsx, ts, mts, cts, tsx]")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("no-ignore")
+                .long("no-ignore")
+                .help("also check files ignored by .gitignore and hidden files")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("jobs")
+                .long("jobs")
+                .short('j')
+                .value_name("N")
+                .help("number of files analysed in parallel [default: one per CPU]")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("timings")
+                .long("timings")
+                .help("print the time spent on each file to stderr")
+                .action(ArgAction::SetTrue),
+        )
         .color(ColorChoice::Always)
         .arg_required_else_help(true)
         .get_matches();
 
-    if let Some(path) = match_result.get_one::<String>("path") {
-        if let Ok(mut f) = File::open(path) {
-            let mut code = String::new();
-            f.read_to_string(&mut code)?;
+    let compat = match match_result.get_one::<String>("compat-data") {
+        Some(path) => match CompatTable::from_bcd_file(path) {
+            Ok(table) => Some(table),
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        None if match_result.get_flag("engines") || match_result.get_flag("dump-compat-data") => {
+            Some(CompatTable::embedded().clone())
+        }
+        None => None,
+    };
+    if match_result.get_flag("dump-compat-data") {
+        print!("{}", compat.expect("table is loaded").to_json());
+        return Ok(());
+    }
+
+    let Some(paths) = match_result.get_many::<String>("paths") else {
+        return Ok(());
+    };
+    let paths: Vec<&String> = paths.collect();
+    let target = match_result.get_one::<EsVersion>("target").copied();
+    let format = *match_result
+        .get_one::<OutputFormat>("format")
+        .expect("format has a default value");
+
+    let strings = |id: &str| -> Vec<String> {
+        match_result
+            .get_many::<String>(id)
+            .map(|v| v.cloned().collect())
+            .unwrap_or_default()
+    };
+    let mut filter = FileFilter {
+        include: strings("include"),
+        exclude: strings("exclude"),
+        respect_ignore_files: !match_result.get_flag("no-ignore"),
+        ..Default::default()
+    };
+    let extensions = strings("ext");
+    if !extensions.is_empty() {
+        filter.extensions = extensions
+            .into_iter()
+            .map(|e| e.trim_start_matches('.').to_string())
+            .collect();
+    }
+
+    let files = match js_version_checker_lib::find_js_files(&paths, &filter) {
+        Ok(files) => files,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let tsconfig_target = match match_result.get_one::<String>("tsconfig") {
+        Some(path) => match read_tsconfig_target(path) {
+            Ok(target) => target,
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    let query = match match_result.get_one::<String>("browserslist") {
+        Some(path) => {
+            let env = std::env::var("BROWSERSLIST_ENV")
+                .or_else(|_| std::env::var("NODE_ENV"))
+                .unwrap_or_else(|_| "production".into());
+            match read_browserslist_config(path, &env) {
+                Ok(Some(query)) => Some(query),
+                Ok(None) => {
+                    error!("No browserslist config found in {}", path);
+                    std::process::exit(2);
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        None => match_result.get_one::<String>("browsers").cloned(),
+    };
+    let browsers = match query {
+        Some(query) => {
+            let resolved = match match_result.get_one::<String>("usage-data") {
+                Some(path) => BrowserData::from_caniuse_file(path).and_then(|d| d.resolve(&query)),
+                None => BrowserData::embedded().resolve(&query),
+            };
+            match resolved {
+                Ok(browsers) if browsers.is_empty() => {
+                    error!("The browserslist query {} selects no browsers", query);
+                    std::process::exit(2);
+                }
+                Ok(browsers) => browsers,
+                Err(e) => {
+                    error!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        None => Vec::new(),
+    };
+    let unknown: Vec<String> = browsers
+        .iter()
+        .filter(|b| !b.has_compat_data())
+        .map(|b| b.to_string())
+        .collect();
+    if !unknown.is_empty() {
+        warn!(
+            "No compatibility data for {}, they are not checked",
+            unknown.join(", ")
+        );
+    }
 
-            let version = js_version_checker_lib::get_min_ecma_version(&code)?;
-            println!("Requires at least {:?} to run", version);
+    let analyzer = Analyzer::new()
+        .with_target(target)
+        .with_goal(
+            *match_result
+                .get_one::<ParseGoal>("goal")
+                .expect("goal has a default value"),
+        )
+        .with_jsx(match_result.get_flag("jsx"))
+        .with_typescript(match_result.get_flag("ts"))
+        .with_tsconfig_target(tsconfig_target)
+        .with_proposals(match_result.get_flag("proposals"))
+        .with_count_conditional(match_result.get_flag("count-conditional"))
+        .with_compat_table(compat.map(Arc::new))
+        .with_browsers(browsers)
+        .with_threads(match_result.get_one::<usize>("jobs").copied());
+    let results = match analyzer.analyse_files(&files) {
+        Ok(results) => results,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(2);
+        }
+    };
 
-            let features = js_version_checker_lib::get_ecma_features(&code)?;
-            println!("language features: {:?}", features);
-        } else {
-            error!("Failed to open file: {}", path);
+    let mut failed_to_read = false;
+    let mut reports = Vec::new();
+    for result in results {
+        if match_result.get_flag("timings") {
+            eprintln!(
+                "{}: {:.3} ms",
+                result.path.display(),
+                result.duration.as_secs_f64() * 1000.0
+            );
+        }
+        match result.report {
+            Ok(report) => reports.push(report),
+            Err(e) => {
+                error!("{}", e);
+                failed_to_read = true;
+            }
         }
     }
 
+    print!("{}", js_version_checker_lib::render(&reports, format));
+
+    if failed_to_read
+        || reports
+            .iter()
+            .any(|r| r.diagnostics.iter().any(|d| !d.recoverable))
+    {
+        std::process::exit(2);
+    }
+    if !reports.iter().all(|r| r.passed()) {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+use strum_macros::{Display, EnumIter, EnumString};
+
+use crate::es_features::FeatureKind;
+use crate::es_version::EsVersion;
+use crate::report::{FeatureReport, Report};
+
+/// Version of the JSON and NDJSON output schema
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Formats reports can be rendered in
+#[derive(Display, EnumString, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+    Sarif,
+}
+
+/// Renders reports in the given format. The output is stable for the same input.
+pub fn render(reports: &[Report], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => render_text(reports),
+        OutputFormat::Json => render_json(reports),
+        OutputFormat::Ndjson => render_ndjson(reports),
+        OutputFormat::Sarif => render_sarif(reports),
+    }
+}
+
+fn render_text(reports: &[Report]) -> String {
+    let mut out = String::new();
+    for report in reports {
+        for diagnostic in &report.diagnostics {
+            let level = if diagnostic.recoverable {
+                "warning"
+            } else {
+                "error"
+            };
+            out.push_str(&format!("{}: {}\n", level, diagnostic));
+        }
+        for warning in &report.warnings {
+            out.push_str(&format!("warning: {}: {}\n", report.file, warning));
+        }
+        let Some(min_version) = report.min_version else {
+            continue;
+        };
+        out.push_str(&format!(
+            "{}: requires at least {}",
+            report.file, min_version
+        ));
+        if let Some(feature) = report.newest_feature() {
+            out.push_str(&format!(" ({})", feature));
+        }
+        if report.requires_module {
+            out.push_str(" as a module");
+        }
+        if report.proposals().next().is_some() {
+            out.push_str(" and proposals not in any edition");
+        }
+        if let Some(after_polyfills) = report
+            .min_version_after_polyfills
+            .filter(|v| *v < min_version)
+        {
+            out.push_str(&format!(", {} with its polyfills", after_polyfills));
+        }
+        let by_kind: Vec<String> = report
+            .min_version_by_kind
+            .iter()
+            .filter(|(_, v)| **v > EsVersion::ES3)
+            .map(|(kind, v)| format!("{} {}", kind_label(*kind), v))
+            .collect();
+        if by_kind.len() > 1 {
+            out.push_str(&format!("; {}", by_kind.join(", ")));
+        }
+        out.push('\n');
+        if !report.engines.is_empty() {
+            let engines: Vec<String> = report
+                .engines
+                .iter()
+                .map(|(engine, version)| match version {
+                    Some(version) => format!("{} {}", engine, version),
+                    None => format!("{} unsupported", engine),
+                })
+                .collect();
+            out.push_str(&format!("  engines: {}\n", engines.join(", ")));
+        }
+
+        let mut occurrences: Vec<_> = report
+            .features
+            .iter()
+            .filter(|f| !checked(report) || f.above_target)
+            .flat_map(|f| f.locations.iter().map(move |l| (l, f)))
+            .collect();
+        occurrences.sort_by(|a, b| (a.0, a.1.feature).cmp(&(b.0, b.1.feature)));
+        for (location, f) in occurrences {
+            match violation(report, f) {
+                Some(violation) => out.push_str(&format!("  {}: {}\n", location, violation)),
+                None => out.push_str(&format!(
+                    "  {}: {} ({}{})\n",
+                    location,
+                    f.feature,
+                    f.version
+                        .map_or("proposal, not in any edition".into(), |v| v.to_string()),
+                    if f.conditional {
+                        ", guarded"
+                    } else if f.polyfilled {
+                        ", polyfilled"
+                    } else {
+                        ""
+                    }
+                )),
+            }
+        }
+    }
+
+    if reports.len() > 1 {
+        if let Some(min_version) = reports.iter().filter_map(|r| r.min_version).max() {
+            out.push_str(&format!(
+                "{} files: require at least {}\n",
+                reports.len(),
+                min_version
+            ));
+        }
+    }
+    out
+}
+
+/// Plural label of a kind of feature for text output
+fn kind_label(kind: FeatureKind) -> &'static str {
+    match kind {
+        FeatureKind::Syntax => "syntax",
+        FeatureKind::BuiltIn => "built-ins",
+        FeatureKind::Lexical => "lexical",
+    }
+}
+
+/// `true` if the report has a target or browsers to check against
+fn checked(report: &Report) -> bool {
+    report.target.is_some() || !report.browsers.is_empty()
+}
+
+/// Why a feature above the target fails, "Feature is unsupported by safari 13.1" or
+/// "Feature requires ES20xx, target is ES20xx"
+fn violation(report: &Report, f: &FeatureReport) -> Option<String> {
+    if !f.above_target || f.polyfilled {
+        return None;
+    }
+    if !f.unsupported_browsers.is_empty() {
+        let browsers: Vec<String> = f
+            .unsupported_browsers
+            .iter()
+            .map(|b| b.to_string())
+            .collect();
+        return Some(format!(
+            "{} is unsupported by {}",
+            f.feature,
+            browsers.join(", ")
+        ));
+    }
+    let target = report.target?;
+    Some(format!("{}, target is {}", requirement(f), target))
+}
+
+/// "Feature requires ES20xx", or that the feature is only a proposal
+fn requirement(f: &FeatureReport) -> String {
+    match f.version {
+        Some(version) => format!("{} requires {}", f.feature, version),
+        None => format!("{} is a proposal, not in any edition", f.feature),
+    }
+}
+
+fn render_json(reports: &[Report]) -> String {
+    let min_version = reports.iter().filter_map(|r| r.min_version).max();
+    let min_version_after_polyfills = reports
+        .iter()
+        .filter_map(|r| r.min_version_after_polyfills)
+        .max();
+    let mut min_version_by_kind: BTreeMap<FeatureKind, EsVersion> = BTreeMap::new();
+    for (kind, version) in reports.iter().flat_map(|r| &r.min_version_by_kind) {
+        let min_version = min_version_by_kind.entry(*kind).or_insert(*version);
+        *min_version = (*min_version).max(*version);
+    }
+    let value = json!({
+        "schema_version": SCHEMA_VERSION,
+        "min_version": min_version,
+        "min_version_after_polyfills": min_version_after_polyfills,
+        "min_version_by_kind": min_version_by_kind,
+        "passed": reports.iter().all(Report::passed),
+        "files": reports,
+    });
+    format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+}
+
+fn render_ndjson(reports: &[Report]) -> String {
+    reports
+        .iter()
+        .map(|r| {
+            let mut value = serde_json::to_value(r).unwrap();
+            value["schema_version"] = json!(SCHEMA_VERSION);
+            format!("{}\n", value)
+        })
+        .collect()
+}
+
+fn render_sarif(reports: &[Report]) -> String {
+    let mut rules: Vec<Value> = Vec::new();
+    let mut rule_ids: Vec<String> = Vec::new();
+    let mut results: Vec<Value> = Vec::new();
+
+    let mut add_rule = |id: String, description: String| {
+        if !rule_ids.contains(&id) {
+            rules.push(json!({
+                "id": id,
+                "shortDescription": { "text": description },
+            }));
+            rule_ids.push(id);
+        }
+    };
+
+    for report in reports {
+        for diagnostic in &report.diagnostics {
+            add_rule("parse-error".into(), "Syntax error".into());
+            results.push(json!({
+                "ruleId": "parse-error",
+                "level": if diagnostic.recoverable { "warning" } else { "error" },
+                "message": { "text": diagnostic.message },
+                "locations": [sarif_location(&report.file, diagnostic.location.line, diagnostic.location.column)],
+            }));
+        }
+        for warning in &report.warnings {
+            add_rule("warning".into(), "Analysis warning".into());
+            results.push(json!({
+                "ruleId": "warning",
+                "level": "warning",
+                "message": { "text": warning },
+                "locations": [{ "physicalLocation": { "artifactLocation": { "uri": report.file } } }],
+            }));
+        }
+        for f in &report.features {
+            add_rule(f.feature.to_string(), requirement(f));
+            for location in &f.locations {
+                let violation = violation(report, f);
+                results.push(json!({
+                    "ruleId": f.feature.to_string(),
+                    "level": if violation.is_some() { "error" } else { "note" },
+                    "message": { "text": violation.unwrap_or_else(|| requirement(f)) },
+                    "locations": [sarif_location(&report.file, location.line, location.column)],
+                }));
+            }
+        }
+    }
+
+    let value = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    });
+    format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+}
+
+fn sarif_location(file: &str, line: usize, column: usize) -> Value {
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": file },
+            "region": { "startLine": line, "startColumn": column },
+        }
+    })
+}
@@ -0,0 +1,34 @@
+use serde::Serialize;
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// The grammar a source is parsed with
+#[derive(
+    Display, EnumString, EnumIter, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum ParseGoal {
+    /// Classic script, `import`/`export` are syntax errors
+    Script,
+    /// ES module, always strict mode
+    Module,
+    /// Decided by the file extension, then by the content of the file
+    #[default]
+    Auto,
+}
+
+impl ParseGoal {
+    /// Resolves `Auto` by the extension of `file_name`: `.mjs` and `.mts` are modules,
+    /// `.cjs` and `.cts` scripts. Other extensions keep `Auto`, leaving the decision to the content.
+    pub fn for_file(self, file_name: &str) -> ParseGoal {
+        match self {
+            ParseGoal::Auto if file_name.ends_with(".mjs") || file_name.ends_with(".mts") => {
+                ParseGoal::Module
+            }
+            ParseGoal::Auto if file_name.ends_with(".cjs") || file_name.ends_with(".cts") => {
+                ParseGoal::Script
+            }
+            goal => goal,
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::aliases::{prop_name, Aliases, GLOBAL_OBJECTS};
+use crate::es_features::EsFeature;
+
+/// Built-ins by their core-js module path, `core-js/es/promise/any` and
+/// `core-js/modules/es.promise.any` both polyfill `promise/any`. Assignments like
+/// `Promise.any = …` or `Array.prototype.flat = …` are looked up by the same path.
+const POLYFILLS: [(&str, EsFeature); 92] = [
+    ("array/at", EsFeature::At),
+    ("array/copy-within", EsFeature::Es2015BuiltIns),
+    ("array/fill", EsFeature::Es2015BuiltIns),
+    ("array/find", EsFeature::Es2015BuiltIns),
+    ("array/find-index", EsFeature::Es2015BuiltIns),
+    ("array/find-last", EsFeature::ArrayFindFromLast),
+    ("array/find-last-index", EsFeature::ArrayFindFromLast),
+    ("array/flat", EsFeature::ArrayPrototypeFlatFlatMap),
+    ("array/flat-map", EsFeature::ArrayPrototypeFlatFlatMap),
+    ("array/from", EsFeature::Es2015BuiltIns),
+    ("array/from-async", EsFeature::ArrayFromAsync),
+    ("array/includes", EsFeature::ArrayPrototypeIncludes),
+    ("array/of", EsFeature::Es2015BuiltIns),
+    ("array/to-reversed", EsFeature::ChangeArrayByCopy),
+    ("array/to-sorted", EsFeature::ChangeArrayByCopy),
+    ("array/to-spliced", EsFeature::ChangeArrayByCopy),
+    ("array/with", EsFeature::ChangeArrayByCopy),
+    ("array-buffer/detached", EsFeature::ArrayBufferTransfer),
+    ("array-buffer/transfer", EsFeature::ArrayBufferTransfer),
+    (
+        "array-buffer/transfer-to-fixed-length",
+        EsFeature::ArrayBufferTransfer,
+    ),
+    (
+        "async-disposable-stack",
+        EsFeature::ExplicitResourceManagementBuiltIns,
+    ),
+    (
+        "disposable-stack",
+        EsFeature::ExplicitResourceManagementBuiltIns,
+    ),
+    ("error/is-error", EsFeature::ErrorIsError),
+    ("finalization-registry", EsFeature::WeakRefs),
+    ("global-this", EsFeature::GlobalThis),
+    ("iterator", EsFeature::IteratorHelpers),
+    ("iterator/drop", EsFeature::IteratorHelpers),
+    ("iterator/every", EsFeature::IteratorHelpers),
+    ("iterator/filter", EsFeature::IteratorHelpers),
+    ("iterator/find", EsFeature::IteratorHelpers),
+    ("iterator/flat-map", EsFeature::IteratorHelpers),
+    ("iterator/for-each", EsFeature::IteratorHelpers),
+    ("iterator/from", EsFeature::IteratorHelpers),
+    ("iterator/map", EsFeature::IteratorHelpers),
+    ("iterator/reduce", EsFeature::IteratorHelpers),
+    ("iterator/some", EsFeature::IteratorHelpers),
+    ("iterator/take", EsFeature::IteratorHelpers),
+    ("iterator/to-array", EsFeature::IteratorHelpers),
+    ("map", EsFeature::KeyedCollections),
+    ("map/group-by", EsFeature::ArrayGrouping),
+    ("math/sum-precise", EsFeature::MathSumPrecise),
+    ("math/sign", EsFeature::Es2015BuiltIns),
+    ("math/trunc", EsFeature::Es2015BuiltIns),
+    ("number/is-integer", EsFeature::Es2015BuiltIns),
+    ("object/assign", EsFeature::Es2015BuiltIns),
+    ("object/entries", EsFeature::ObjectValuesEntries),
+    ("object/from-entries", EsFeature::ObjectFromEntries),
+    (
+        "object/get-own-property-descriptors",
+        EsFeature::ObjectGetOwnPropertyDescriptors,
+    ),
+    ("object/group-by", EsFeature::ArrayGrouping),
+    (
+        "object/has-own",
+        EsFeature::AccessibleObjectPrototypeHasOwnProperty,
+    ),
+    ("object/is", EsFeature::Es2015BuiltIns),
+    ("object/values", EsFeature::ObjectValuesEntries),
+    ("promise", EsFeature::Promises),
+    ("promise/all-settled", EsFeature::PromiseAllSettled),
+    ("promise/any", EsFeature::PromiseAny),
+    ("promise/finally", EsFeature::PromisePrototypeFinally),
+    ("promise/try", EsFeature::PromiseTry),
+    ("promise/with-resolvers", EsFeature::PromiseWithResolvers),
+    ("reflect", EsFeature::Reflect),
+    ("regexp/escape", EsFeature::RegExpEscaping),
+    ("set", EsFeature::KeyedCollections),
+    ("set/difference", EsFeature::NetSetMethods),
+    ("set/intersection", EsFeature::NetSetMethods),
+    ("set/is-disjoint-from", EsFeature::NetSetMethods),
+    ("set/is-subset-of", EsFeature::NetSetMethods),
+    ("set/is-superset-of", EsFeature::NetSetMethods),
+    ("set/symmetric-difference", EsFeature::NetSetMethods),
+    ("set/union", EsFeature::NetSetMethods),
+    ("string/at", EsFeature::At),
+    ("string/is-well-formed", EsFeature::WellFormedUnicodeStrings),
+    ("string/match-all", EsFeature::StringPrototypeMatchAll),
+    ("string/pad-end", EsFeature::StringPadding),
+    ("string/pad-start", EsFeature::StringPadding),
+    ("string/replace-all", EsFeature::StringPrototypeReplaceAll),
+    ("string/to-well-formed", EsFeature::WellFormedUnicodeStrings),
+    (
+        "string/trim-end",
+        EsFeature::StringPrototypeTrimStartTrimEnd,
+    ),
+    (
+        "string/trim-start",
+        EsFeature::StringPrototypeTrimStartTrimEnd,
+    ),
+    ("symbol", EsFeature::Symbols),
+    (
+        "symbol/async-dispose",
+        EsFeature::ExplicitResourceManagementBuiltIns,
+    ),
+    ("symbol/async-iterator", EsFeature::SymbolAsyncIterator),
+    ("symbol/description", EsFeature::SymbolPrototypeDescription),
+    (
+        "symbol/dispose",
+        EsFeature::ExplicitResourceManagementBuiltIns,
+    ),
+    ("typed-array", EsFeature::TypedArrays),
+    ("uint8-array/from-base64", EsFeature::Uint8ArrayBase64),
+    ("uint8-array/from-hex", EsFeature::Uint8ArrayBase64),
+    ("uint8-array/set-from-base64", EsFeature::Uint8ArrayBase64),
+    ("uint8-array/set-from-hex", EsFeature::Uint8ArrayBase64),
+    ("uint8-array/to-base64", EsFeature::Uint8ArrayBase64),
+    ("uint8-array/to-hex", EsFeature::Uint8ArrayBase64),
+    ("weak-map", EsFeature::KeyedCollections),
+    ("weak-ref", EsFeature::WeakRefs),
+    ("weak-set", EsFeature::KeyedCollections),
+];
+
+/// Modules that polyfill the whole standard library
+const FULL_POLYFILLS: [&str; 10] = [
+    "core-js",
+    "core-js/es",
+    "core-js/stable",
+    "core-js/actual",
+    "core-js/full",
+    "core-js/features",
+    "core-js-bundle",
+    "@babel/polyfill",
+    "babel-polyfill",
+    "@babel/polyfill/noConflict",
+];
+
+/// Runtime of generators and async functions transpiled to ES5. It is recognized as a polyfill,
+/// but covers no feature: generators are syntax, which is gone from a transpiled file.
+const REGENERATOR_RUNTIME: [&str; 2] = ["regenerator-runtime", "regenerator-runtime/runtime"];
+
+/// Built-ins a file provides itself, by importing core-js and friends or by assigning to the
+/// built-in like `Object.fromEntries = …`
+#[derive(Debug, Default)]
+pub struct Polyfills {
+    /// Polyfill modules imported or required, in source order
+    pub sources: Vec<String>,
+    // core-js paths of built-ins assigned like `Math.sign = …`, without their members
+    built_ins: HashSet<String>,
+    // core-js paths of imported modules like `promise`, with all their members
+    modules: HashSet<String>,
+}
+
+impl Polyfills {
+    /// Collects the polyfills imported, required or defined anywhere in the program
+    pub fn collect(program: &Program, aliases: &Aliases) -> Self {
+        let mut collector = Collector {
+            aliases,
+            polyfills: Polyfills::default(),
+        };
+        program.visit_with(&mut collector);
+        collector.polyfills
+    }
+
+    /// `true` if the built-in an occurrence of `feature` refers to is polyfilled, for references
+    /// like `Promise.any`, `Array.prototype.flat` or `prototype.flat` on an unknown object
+    pub fn covers(&self, feature: EsFeature, reference: &str) -> bool {
+        match reference.strip_prefix("prototype.") {
+            // any built-in with the method that provides the feature
+            Some(method) => {
+                let method = kebab_case(method);
+                POLYFILLS.iter().any(|(path, f)| {
+                    *f == feature
+                        && path.rsplit_once('/').is_some_and(|(_, m)| m == method)
+                        && self.covers_path(feature, path)
+                })
+            }
+            None => {
+                let path: Vec<String> = reference
+                    .split('.')
+                    .filter(|s| *s != "prototype")
+                    .map(kebab_case)
+                    .collect();
+                self.covers_path(feature, &path.join("/"))
+            }
+        }
+    }
+
+    /// Modules cover all their members, an assigned built-in like `window.Promise = …` only
+    /// the members of its own feature, not `Promise.any`
+    fn covers_path(&self, feature: EsFeature, path: &str) -> bool {
+        let below = |parent: &str| {
+            path.strip_prefix(parent)
+                .is_some_and(|m| m.is_empty() || m.starts_with('/'))
+        };
+        self.modules.iter().any(|module| below(module))
+            || self.built_ins.iter().any(|built_in| {
+                built_in == path
+                    || (below(built_in) && POLYFILLS.contains(&(built_in.as_str(), feature)))
+            })
+    }
+
+    /// Records a module specifier if it is a polyfill
+    fn add_source(&mut self, src: &str) {
+        let src = src.trim_end_matches(".js").trim_end_matches("/index");
+        if FULL_POLYFILLS.contains(&src) {
+            self.modules
+                .extend(POLYFILLS.iter().map(|(path, _)| path.to_string()));
+        } else if let Some(path) = core_js_path(src) {
+            self.modules.insert(path);
+        } else if !REGENERATOR_RUNTIME.contains(&src) {
+            return;
+        }
+        self.sources.push(src.to_string());
+    }
+}
+
+struct Collector<'a> {
+    aliases: &'a Aliases,
+    polyfills: Polyfills,
+}
+
+impl Collector<'_> {
+    /// Path of `prop` on `obj`, for `Promise.any`, `Array.prototype.flat` and `window.WeakRef`
+    fn built_in_path(&self, obj: &Expr, prop: &str) -> Option<String> {
+        if let Some(global) = self.aliases.global_name(obj) {
+            return Some(if GLOBAL_OBJECTS.contains(&global.as_str()) {
+                kebab_case(prop)
+            } else {
+                format!("{}/{}", kebab_case(&global), kebab_case(prop))
+            });
+        }
+        match obj {
+            Expr::Member(m) if prop_name(&m.prop).is_some_and(|p| p == "prototype") => {
+                let global = self.aliases.global_name(&m.obj)?;
+                Some(format!("{}/{}", kebab_case(&global), kebab_case(prop)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Visit for Collector<'_> {
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        if let AssignTarget::Simple(SimpleAssignTarget::Member(m)) = &n.left {
+            if let Some(path) = prop_name(&m.prop).and_then(|p| self.built_in_path(&m.obj, &p)) {
+                self.polyfills.built_ins.insert(path);
+            }
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        let first_string = match n.args.first().map(|a| a.expr.deref()) {
+            Some(Expr::Lit(Lit::Str(s))) => Some(s.value.to_string()),
+            _ => None,
+        };
+        match &n.callee {
+            Callee::Import(_) => {
+                if let Some(src) = first_string {
+                    self.polyfills.add_source(&src);
+                }
+            }
+            Callee::Expr(callee) => {
+                if let Some(src) = first_string.filter(|_| {
+                    self.aliases
+                        .global_name(callee)
+                        .is_some_and(|c| c == "require")
+                }) {
+                    self.polyfills.add_source(&src);
+                }
+                // `Object.defineProperty(Array.prototype, 'flat', …)`
+                if self
+                    .aliases
+                    .static_member(callee)
+                    .is_some_and(|(o, m)| o == "Object" && m == "defineProperty")
+                {
+                    if let [target, ExprOrSpread { expr: prop, .. }, ..] = n.args.as_slice() {
+                        if let Expr::Lit(Lit::Str(prop)) = prop.deref() {
+                            if let Some(path) = self.built_in_path(&target.expr, &prop.value) {
+                                self.polyfills.built_ins.insert(path);
+                            }
+                        }
+                    }
+                }
+            }
+            Callee::Super(_) => {}
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_import_decl(&mut self, n: &ImportDecl) {
+        self.polyfills.add_source(&n.src.value);
+    }
+}
+
+/// Path below the entry point for core-js modules, `core-js/stable/array/flat` and
+/// `core-js/modules/es.array.flat` become `array/flat`
+fn core_js_path(src: &str) -> Option<String> {
+    let path = src.strip_prefix("core-js/")?;
+    if let Some(module) = path.strip_prefix("modules/") {
+        let module = module
+            .strip_prefix("es.")
+            .or_else(|| module.strip_prefix("esnext."))?;
+        return Some(module.replacen('.', "/", 1));
+    }
+    let (entry, path) = path.split_once('/')?;
+    if !["es", "stable", "actual", "full", "features"].contains(&entry) {
+        return None;
+    }
+    Some(path.replace("/virtual", ""))
+}
+
+/// `fromEntries` to `from-entries` and `WeakRef` to `weak-ref`, like core-js names its modules
+fn kebab_case(name: &str) -> String {
+    if name == "RegExp" {
+        return "regexp".into();
+    }
+    let mut kebab = String::new();
+    for (i, c) in name.char_indices() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                kebab.push('-');
+            }
+            kebab.push(c.to_ascii_lowercase());
+        } else {
+            kebab.push(c);
+        }
+    }
+    kebab
+}
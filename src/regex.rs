@@ -0,0 +1,530 @@
+//! Parser for ECMAScript regular expression patterns.
+//! Only builds as much structure as feature detection needs, character values are kept but not validated.
+
+use std::collections::HashSet;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::es_features::EsFeature;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    /// `a|b`
+    Disjunction(Vec<Node>),
+    /// Terms matched in sequence
+    Alternative(Vec<Node>),
+    /// `^`, `$`, `\b` and `\B`
+    Assertion(char),
+    /// `(?=...)`, `(?!...)`, `(?<=...)` and `(?<!...)`
+    Lookaround {
+        behind: bool,
+        negative: bool,
+        body: Box<Node>,
+    },
+    /// `(...)`, `(?<name>...)`, `(?:...)` and `(?ims-ims:...)`
+    Group {
+        capturing: bool,
+        name: Option<String>,
+        modifiers: Option<String>,
+        body: Box<Node>,
+    },
+    Quantifier {
+        body: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+    },
+    Char(char),
+    /// `.`
+    Dot,
+    /// `\d`, `\D`, `\w`, `\W`, `\s` and `\S`
+    CharClassEscape(char),
+    /// `\p{...}` and `\P{...}`, only in unicode mode
+    PropertyEscape {
+        negated: bool,
+        name: String,
+    },
+    /// `\1`
+    BackReference(u32),
+    /// `\k<name>`
+    NamedBackReference(String),
+    /// `[...]`
+    Class {
+        negated: bool,
+        items: Vec<Node>,
+    },
+    /// `a-z` inside a class
+    Range(char, char),
+    /// `&&` and `--` inside a class, only with the `v` flag
+    SetOperator(&'static str),
+    /// `\q{abc|d}` inside a class, only with the `v` flag
+    ClassStrings(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegexError {
+    pub message: String,
+}
+
+type ParseResult<T> = Result<T, RegexError>;
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// `u` or `v` flag
+    unicode: bool,
+    /// `v` flag
+    unicode_sets: bool,
+    /// `\k<name>` is a back-reference only in unicode mode or if the pattern has named groups
+    named_groups: bool,
+}
+
+/// Parses a pattern as it would be interpreted with the given flags
+pub(crate) fn parse(pattern: &str, flags: &str) -> ParseResult<Node> {
+    let mut parser = Parser {
+        chars: pattern.chars().peekable(),
+        unicode: flags.contains('u') || flags.contains('v'),
+        unicode_sets: flags.contains('v'),
+        named_groups: has_named_group(pattern),
+    };
+    let node = parser.disjunction()?;
+    match parser.chars.next() {
+        None => Ok(node),
+        Some(c) => Err(parser.error(format!("unexpected '{}'", c))),
+    }
+}
+
+/// Detects the features used by a regular expression with the given flags
+pub(crate) fn features(pattern: Option<&str>, flags: &str) -> Vec<EsFeature> {
+    let mut features = Vec::new();
+    if flags.contains('u') {
+        features.push(EsFeature::RegExpUnicodeFlag);
+    }
+    if flags.contains('y') {
+        features.push(EsFeature::RegExpStickyFlag);
+    }
+    if flags.contains('s') {
+        features.push(EsFeature::SDotAllFlagForRegularExpressions);
+    }
+    if flags.contains('d') {
+        features.push(EsFeature::RegExpMatchIndices);
+    }
+    if flags.contains('v') {
+        features.push(EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings);
+    }
+
+    if let Some(Ok(node)) = pattern.map(|p| parse(p, flags)) {
+        let mut names = HashSet::new();
+        collect_features(&node, &mut names, &mut features);
+    }
+    features.sort();
+    features.dedup();
+    features
+}
+
+fn collect_features(node: &Node, names: &mut HashSet<String>, features: &mut Vec<EsFeature>) {
+    match node {
+        Node::Disjunction(nodes) | Node::Alternative(nodes) | Node::Class { items: nodes, .. } => {
+            for node in nodes {
+                collect_features(node, names, features);
+            }
+        }
+        Node::Lookaround { behind, body, .. } => {
+            if *behind {
+                features.push(EsFeature::RegExpLookbehindAssertions);
+            }
+            collect_features(body, names, features);
+        }
+        Node::Group {
+            name,
+            modifiers,
+            body,
+            ..
+        } => {
+            if let Some(name) = name {
+                features.push(EsFeature::RegExpNamedCaptureGroups);
+                if !names.insert(name.clone()) {
+                    features.push(EsFeature::DuplicateNamedCaptureGroups);
+                }
+            }
+            if modifiers.is_some() {
+                features.push(EsFeature::RegExpModifiers);
+            }
+            collect_features(body, names, features);
+        }
+        Node::Quantifier { body, .. } => collect_features(body, names, features),
+        Node::PropertyEscape { .. } => features.push(EsFeature::RegExpUnicodePropertyEscapes),
+        Node::NamedBackReference(_) => features.push(EsFeature::RegExpNamedCaptureGroups),
+        Node::Assertion(_)
+        | Node::Char(_)
+        | Node::Dot
+        | Node::CharClassEscape(_)
+        | Node::BackReference(_)
+        | Node::Range(_, _)
+        | Node::SetOperator(_)
+        | Node::ClassStrings(_) => {}
+    }
+}
+
+/// `true` if the pattern contains `(?<name>`, ignoring escaped parentheses and classes
+fn has_named_group(pattern: &str) -> bool {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut in_class = false;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '(' if !in_class
+                && chars.get(i + 1) == Some(&'?')
+                && chars.get(i + 2) == Some(&'<')
+                && !matches!(chars.get(i + 3), Some('=' | '!')) =>
+            {
+                return true;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+impl Parser<'_> {
+    fn error(&self, message: String) -> RegexError {
+        RegexError { message }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if_eq(&c).is_some()
+    }
+
+    fn expect(&mut self, c: char) -> ParseResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", c)))
+        }
+    }
+
+    fn disjunction(&mut self) -> ParseResult<Node> {
+        let mut alternatives = vec![self.alternative()?];
+        while self.eat('|') {
+            alternatives.push(self.alternative()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Node::Disjunction(alternatives)
+        })
+    }
+
+    fn alternative(&mut self) -> ParseResult<Node> {
+        let mut terms = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            terms.push(self.quantifier(atom)?);
+        }
+        Ok(Node::Alternative(terms))
+    }
+
+    fn atom(&mut self) -> ParseResult<Node> {
+        let c = self.chars.next().expect("caller checked for more input");
+        match c {
+            '^' | '$' => Ok(Node::Assertion(c)),
+            '.' => Ok(Node::Dot),
+            '(' => self.group(),
+            '[' => self.class(),
+            '\\' => self.atom_escape(),
+            '*' | '+' | '?' => Err(self.error(format!("nothing to repeat before '{}'", c))),
+            '{' if self.unicode => Err(self.error("lone quantifier brackets".into())),
+            ']' | '}' if self.unicode => Err(self.error(format!("lone '{}'", c))),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn group(&mut self) -> ParseResult<Node> {
+        if !self.eat('?') {
+            let body = self.disjunction()?;
+            self.expect(')')?;
+            return Ok(Node::Group {
+                capturing: true,
+                name: None,
+                modifiers: None,
+                body: Box::new(body),
+            });
+        }
+
+        let node = match self.chars.next() {
+            Some('=') => self.lookaround(false, false)?,
+            Some('!') => self.lookaround(false, true)?,
+            Some('<') if self.eat('=') => self.lookaround(true, false)?,
+            Some('<') if self.eat('!') => self.lookaround(true, true)?,
+            Some('<') => {
+                let name = self.group_name()?;
+                Node::Group {
+                    capturing: true,
+                    name: Some(name),
+                    modifiers: None,
+                    body: Box::new(self.disjunction()?),
+                }
+            }
+            Some(':') => Node::Group {
+                capturing: false,
+                name: None,
+                modifiers: None,
+                body: Box::new(self.disjunction()?),
+            },
+            Some(c) if matches!(c, 'i' | 'm' | 's' | '-') => {
+                let mut modifiers = String::from(c);
+                while let Some(c) = self.chars.next_if(|c| matches!(c, 'i' | 'm' | 's' | '-')) {
+                    modifiers.push(c);
+                }
+                self.expect(':')?;
+                Node::Group {
+                    capturing: false,
+                    name: None,
+                    modifiers: Some(modifiers),
+                    body: Box::new(self.disjunction()?),
+                }
+            }
+            _ => return Err(self.error("invalid group".into())),
+        };
+        self.expect(')')?;
+        Ok(node)
+    }
+
+    fn lookaround(&mut self, behind: bool, negative: bool) -> ParseResult<Node> {
+        Ok(Node::Lookaround {
+            behind,
+            negative,
+            body: Box::new(self.disjunction()?),
+        })
+    }
+
+    /// Reads a group name up to and including the closing `>`
+    fn group_name(&mut self) -> ParseResult<String> {
+        let mut name = String::new();
+        loop {
+            match self.chars.next() {
+                Some('>') if !name.is_empty() => return Ok(name),
+                Some(c) if c.is_alphanumeric() || matches!(c, '$' | '_' | '\\' | '{' | '}') => {
+                    name.push(c)
+                }
+                _ => return Err(self.error("invalid group name".into())),
+            }
+        }
+    }
+
+    fn quantifier(&mut self, atom: Node) -> ParseResult<Node> {
+        let single = matches!(self.chars.peek(), Some('*' | '+' | '?'));
+        let (min, max) = match self.chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.braced_quantifier() {
+                Some(bounds) => bounds,
+                None if self.unicode => return Err(self.error("incomplete quantifier".into())),
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if single {
+            self.chars.next();
+        }
+        if let Node::Assertion(_) | Node::Lookaround { behind: true, .. } = atom {
+            return Err(self.error("nothing to repeat".into()));
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Quantifier {
+            body: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// Parses `{n}`, `{n,}` or `{n,m}`. Returns `None` without consuming input if the braces
+    /// do not form a quantifier.
+    fn braced_quantifier(&mut self) -> Option<(u32, Option<u32>)> {
+        let rest: String = self.chars.clone().collect();
+        let end = rest.find('}')?;
+        let inner = &rest[1..end];
+        let (min, max) = match inner.split_once(',') {
+            None => {
+                let n = inner.parse().ok()?;
+                (n, Some(n))
+            }
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+        };
+        // everything up to and including the closing brace is ASCII
+        for _ in 0..=end {
+            self.chars.next();
+        }
+        Some((min, max))
+    }
+
+    fn atom_escape(&mut self) -> ParseResult<Node> {
+        let c = self
+            .chars
+            .next()
+            .ok_or_else(|| self.error("\\ at end of pattern".into()))?;
+        match c {
+            'b' | 'B' => Ok(Node::Assertion(c)),
+            '1'..='9' => {
+                let mut n = c.to_digit(10).unwrap();
+                while let Some(d) = self.chars.next_if(|c| c.is_ascii_digit()) {
+                    n = n.saturating_mul(10).saturating_add(d.to_digit(10).unwrap());
+                }
+                Ok(Node::BackReference(n))
+            }
+            'k' if self.unicode || self.named_groups => {
+                self.expect('<')?;
+                Ok(Node::NamedBackReference(self.group_name()?))
+            }
+            c => self.character_escape(c),
+        }
+    }
+
+    /// Escapes valid both inside and outside of classes
+    fn character_escape(&mut self, c: char) -> ParseResult<Node> {
+        match c {
+            'd' | 'D' | 'w' | 'W' | 's' | 'S' => Ok(Node::CharClassEscape(c)),
+            'p' | 'P' if self.unicode => {
+                self.expect('{')?;
+                let mut name = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(self.error("unterminated property escape".into())),
+                    }
+                }
+                Ok(Node::PropertyEscape {
+                    negated: c == 'P',
+                    name,
+                })
+            }
+            'n' => Ok(Node::Char('\n')),
+            'r' => Ok(Node::Char('\r')),
+            't' => Ok(Node::Char('\t')),
+            'v' => Ok(Node::Char('\u{b}')),
+            'f' => Ok(Node::Char('\u{c}')),
+            '0' => Ok(Node::Char('\0')),
+            'c' => match self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+                Some(letter) => Ok(Node::Char(char::from(letter as u8 % 32))),
+                None => Ok(Node::Char('c')),
+            },
+            'x' => Ok(Node::Char(self.hex_digits(2).unwrap_or('x'))),
+            'u' if self.unicode && self.eat('{') => {
+                let mut digits = String::new();
+                while let Some(c) = self.chars.next_if(|c| *c != '}') {
+                    digits.push(c);
+                }
+                self.expect('}')?;
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(Node::Char)
+                    .ok_or_else(|| self.error("invalid unicode escape".into()))
+            }
+            'u' => Ok(Node::Char(self.hex_digits(4).unwrap_or('u'))),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    /// Reads exactly `count` hex digits, consuming nothing if there are fewer
+    fn hex_digits(&mut self, count: usize) -> Option<char> {
+        let digits: String = self.chars.clone().take(count).collect();
+        if digits.len() != count || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        for _ in 0..count {
+            self.chars.next();
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    fn class(&mut self) -> ParseResult<Node> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        loop {
+            let c = self
+                .chars
+                .next()
+                .ok_or_else(|| self.error("unterminated character class".into()))?;
+            let item = match c {
+                ']' => break,
+                '[' if self.unicode_sets => self.class()?,
+                '&' if self.unicode_sets && self.eat('&') => Node::SetOperator("&&"),
+                '-' if self.unicode_sets && self.eat('-') => Node::SetOperator("--"),
+                '\\' => self.class_escape()?,
+                c => Node::Char(c),
+            };
+
+            // ranges like `a-z`, a trailing `-` is a literal
+            let is_char = matches!(item, Node::Char(_));
+            if is_char && self.chars.peek() == Some(&'-') {
+                let mut lookahead = self.chars.clone();
+                lookahead.next();
+                if !matches!(lookahead.peek(), Some(']' | '-') | None) {
+                    self.chars.next();
+                    let end = match self.chars.next() {
+                        Some('\\') => self.class_escape()?,
+                        Some(c) => Node::Char(c),
+                        None => return Err(self.error("unterminated character class".into())),
+                    };
+                    match (item, end) {
+                        (Node::Char(from), Node::Char(to)) => {
+                            if from > to {
+                                return Err(self.error("range out of order".into()));
+                            }
+                            items.push(Node::Range(from, to));
+                        }
+                        (from, to) if !self.unicode => {
+                            // Annex B: `[\d-a]` is a set of three items
+                            items.extend([from, Node::Char('-'), to]);
+                        }
+                        _ => return Err(self.error("invalid character class range".into())),
+                    }
+                    continue;
+                }
+            }
+            items.push(item);
+        }
+        Ok(Node::Class { negated, items })
+    }
+
+    fn class_escape(&mut self) -> ParseResult<Node> {
+        let c = self
+            .chars
+            .next()
+            .ok_or_else(|| self.error("\\ at end of pattern".into()))?;
+        match c {
+            'b' => Ok(Node::Char('\u{8}')),
+            '-' if self.unicode => Ok(Node::Char('-')),
+            'q' if self.unicode_sets && self.eat('{') => {
+                let mut strings = vec![String::new()];
+                loop {
+                    match self.chars.next() {
+                        Some('}') => break,
+                        Some('|') => strings.push(String::new()),
+                        Some('\\') => {
+                            let escaped = self.chars.next().unwrap_or('\\');
+                            strings.last_mut().unwrap().push(escaped)
+                        }
+                        Some(c) => strings.last_mut().unwrap().push(c),
+                        None => return Err(self.error("unterminated \\q{".into())),
+                    }
+                }
+                Ok(Node::ClassStrings(strings))
+            }
+            c => self.character_escape(c),
+        }
+    }
+}
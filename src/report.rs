@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::browserslist::Browser;
+use crate::compat::{Engine, EngineVersion};
+use crate::diagnostic::Diagnostic;
+use crate::es_features::{EsFeature, FeatureKind};
+use crate::es_version::EsVersion;
+use crate::location::Location;
+use crate::parse_goal::ParseGoal;
+
+/// Analysis result of a single file, the unit all output formats are rendered from
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub file: String,
+    /// Minimum version required to run the file, `None` if it could not be parsed
+    pub min_version: Option<EsVersion>,
+    /// Minimum version if the built-ins the file polyfills itself are not counted
+    pub min_version_after_polyfills: Option<EsVersion>,
+    /// Minimum version for the syntax, built-ins and lexical features on their own,
+    /// empty if the file could not be parsed
+    pub min_version_by_kind: BTreeMap<FeatureKind, EsVersion>,
+    /// Minimum version of each engine, `None` if no version supports every feature.
+    /// Empty unless the analyzer has a compatibility table.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub engines: BTreeMap<Engine, Option<EngineVersion>>,
+    pub target: Option<EsVersion>,
+    /// Browsers the file is checked against, resolved from a browserslist query
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub browsers: Vec<Browser>,
+    /// Goal the file was parsed with, `None` if it could not be parsed
+    pub goal: Option<ParseGoal>,
+    /// `true` if the file uses `import`/`export`, top-level `await` or `import.meta`
+    pub requires_module: bool,
+    /// Polyfill modules the file imports or requires, like `core-js/stable`
+    pub polyfills: Vec<String>,
+    /// Detected features, sorted by version and then by feature, proposals last
+    pub features: Vec<FeatureReport>,
+    /// Parser diagnostics, empty if the file was parsed without errors. Features are still
+    /// reported if the parser recovered from all of them.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Problems that do not fail the check
+    pub warnings: Vec<String>,
+}
+
+/// All occurrences of a single feature in a file
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureReport {
+    pub feature: EsFeature,
+    /// Whether the feature is syntax, a built-in or lexical, see [`EsFeature::kind`]
+    pub kind: FeatureKind,
+    /// Edition that introduced the feature, `None` for proposals that are not in any edition
+    pub version: Option<EsVersion>,
+    /// `true` if the feature needs a newer version than the report's target or is unsupported by
+    /// one of its browsers. Proposals are above every target, conditional features never are.
+    /// Polyfilled features can be above the target, but do not fail it.
+    pub above_target: bool,
+    /// `true` if every occurrence is guarded by a feature test like
+    /// `typeof Promise.any === 'function'`, the feature does not raise the minimum version
+    pub conditional: bool,
+    /// `true` if the file polyfills the built-in itself wherever it is used
+    pub polyfilled: bool,
+    /// Browsers of the report that lack the feature
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unsupported_browsers: Vec<Browser>,
+    /// Occurrences sorted by their position in the file
+    pub locations: Vec<Location>,
+}
+
+impl Report {
+    /// `true` if the file was parsed and needs no newer version than the target
+    pub fn passed(&self) -> bool {
+        self.diagnostics.iter().all(|d| d.recoverable)
+            && self
+                .features
+                .iter()
+                .all(|f| !f.above_target || f.polyfilled)
+    }
+
+    /// The feature that requires the newest version, if any.
+    /// Proposals and conditional features are not considered.
+    pub fn newest_feature(&self) -> Option<EsFeature> {
+        self.features
+            .iter()
+            .rev()
+            .find(|f| f.version.is_some() && !f.conditional)
+            .map(|f| f.feature)
+    }
+
+    /// Proposals required by the file, only detected if enabled in the [`crate::Analyzer`]
+    pub fn proposals(&self) -> impl Iterator<Item = &FeatureReport> {
+        self.features
+            .iter()
+            .filter(|f| f.version.is_none() && !f.conditional)
+    }
+
+    /// Built-ins the file polyfills itself
+    pub fn polyfilled(&self) -> impl Iterator<Item = &FeatureReport> {
+        self.features.iter().filter(|f| f.polyfilled)
+    }
+
+    /// Features whose every occurrence is guarded by a feature test
+    pub fn conditional(&self) -> impl Iterator<Item = &FeatureReport> {
+        self.features.iter().filter(|f| f.conditional)
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::es_version::EsVersion;
+use crate::{read_text_file, Error, Result};
+
+/// Reads `compilerOptions.target` from a `tsconfig.json`.
+/// Returns `None` if no target is set, `extends` is not followed.
+pub fn read_tsconfig_target(path: impl AsRef<Path>) -> Result<Option<EsVersion>> {
+    let path = path.as_ref();
+    let config_error = |message: String| Error::Config {
+        path: path.display().to_string(),
+        message,
+    };
+
+    let content = read_text_file(path)?;
+    let json: Value = serde_json::from_str(&strip_json_comments(&content))
+        .map_err(|e| config_error(e.to_string()))?;
+    let Some(target) = json
+        .get("compilerOptions")
+        .and_then(|options| options.get("target"))
+    else {
+        return Ok(None);
+    };
+    let target = target
+        .as_str()
+        .ok_or_else(|| config_error("compilerOptions.target is not a string".into()))?;
+    target
+        .parse::<EsVersion>()
+        .map(Some)
+        .map_err(|_| config_error(format!("unknown target {}", target)))
+}
+
+/// Removes comments and trailing commas, which `tsconfig.json` allows but JSON does not
+fn strip_json_comments(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                out.push(c);
+            }
+        }
+    }
+
+    remove_trailing_commas(&out)
+}
+
+fn remove_trailing_commas(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            match c {
+                '\\' => {
+                    out.push(c);
+                    i += 1;
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}' | ']')) {
+                i += 1;
+                continue;
+            }
+        }
+        if let Some(c) = chars.get(i) {
+            out.push(*c);
+        }
+        i += 1;
+    }
+    out
+}
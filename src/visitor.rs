@@ -1,25 +1,104 @@
 extern crate swc_common;
 extern crate swc_ecma_parser;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::ops::Deref;
 
+use swc_common::sync::Lrc;
+use swc_common::{BytePos, SourceMap, Span, Spanned};
 use swc_ecma_ast::*;
 use swc_ecma_visit::{Visit, VisitWith};
 
+use crate::aliases::{prop_name, Aliases, GLOBAL_OBJECTS};
 use crate::es_features::EsFeature;
+use crate::polyfills::Polyfills;
+use crate::regex;
+
+const TYPED_ARRAYS: [&str; 11] = [
+    "ArrayBuffer",
+    "DataView",
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+];
+
+/// Tested by a `try` block that compiles code at runtime, a missing built-in throws inside it
+const ANY_BUILT_IN: &str = "*";
+
+/// Standard globals whose members are feature tested, next to `TYPED_ARRAYS`
+const BUILT_INS: [&str; 32] = [
+    "Array",
+    "AsyncContext",
+    "AsyncDisposableStack",
+    "Atomics",
+    "BigInt",
+    "BigInt64Array",
+    "BigUint64Array",
+    "DisposableStack",
+    "Error",
+    "FinalizationRegistry",
+    "Float16Array",
+    "Intl",
+    "Iterator",
+    "JSON",
+    "Map",
+    "Math",
+    "Number",
+    "Object",
+    "Promise",
+    "Proxy",
+    "Reflect",
+    "RegExp",
+    "Set",
+    "ShadowRealm",
+    "SharedArrayBuffer",
+    "String",
+    "Symbol",
+    "Temporal",
+    "WeakMap",
+    "WeakRef",
+    "WeakSet",
+    "globalThis",
+];
 
 #[derive(Default)]
 pub struct FeatureFinder {
+    // source of the visited program, the AST has no trailing commas
+    cm: Lrc<SourceMap>,
+    // report Stage 2.7/3 proposals
+    proposals: bool,
+    // TypeScript compiles decorators and erases fields that only declare a type
+    typescript: bool,
+    // resolves references to built-in globals
+    aliases: Aliases,
+    // built-ins the source provides itself
+    polyfills: Polyfills,
     // used to detect top level ´await´
     in_function: bool,
-    set: HashSet<EsFeature>,
+    // set by syntax that is only valid in modules
+    module_syntax: bool,
+    // built-ins tested by the enclosing feature tests, like `Promise.any` or `Array.prototype.at`
+    guarded: Vec<String>,
+    // built-in the visited call, `new` or member expression refers to
+    reference: Option<String>,
+    // every occurrence of a feature, in the order it was visited
+    features: HashMap<EsFeature, Vec<Span>>,
+    // occurrences of built-ins behind a feature test, they have a fallback
+    conditional: HashMap<EsFeature, Vec<Span>>,
+    // occurrences of built-ins the source polyfills, required or conditional
+    polyfilled: HashMap<EsFeature, Vec<Span>>,
 }
 
 impl Debug for FeatureFinder {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for e in &self.set {
+        for e in self.features.keys() {
             write!(f, "{}", e)?
         }
         Ok(())
@@ -27,31 +106,346 @@ impl Debug for FeatureFinder {
 }
 
 impl FeatureFinder {
+    pub fn new(
+        cm: Lrc<SourceMap>,
+        proposals: bool,
+        typescript: bool,
+        aliases: Aliases,
+        polyfills: Polyfills,
+    ) -> Self {
+        FeatureFinder {
+            cm,
+            proposals,
+            typescript,
+            aliases,
+            polyfills,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the features that are required, guarded usages are not included
     pub fn get_result(&self) -> HashSet<EsFeature> {
-        self.set.to_owned()
+        self.features.keys().copied().collect()
+    }
+
+    /// `true` if the source can only be run as a module
+    pub fn requires_module(&self) -> bool {
+        self.module_syntax
+    }
+
+    /// Returns the spans of all occurrences per feature
+    pub fn get_occurrences(&self) -> HashMap<EsFeature, Vec<Span>> {
+        self.features.to_owned()
+    }
+
+    /// Returns the spans of all occurrences per feature that are guarded by a feature test
+    pub fn get_conditional_occurrences(&self) -> HashMap<EsFeature, Vec<Span>> {
+        self.conditional.to_owned()
+    }
+
+    /// Returns the polyfills of the source
+    pub fn get_polyfills(&self) -> &Polyfills {
+        &self.polyfills
+    }
+
+    /// Returns the built-ins the source polyfills wherever it uses them
+    pub fn get_polyfilled(&self) -> HashSet<EsFeature> {
+        self.polyfilled
+            .iter()
+            .filter(|(feature, spans)| {
+                let occurrences = [&self.features, &self.conditional]
+                    .iter()
+                    .filter_map(|o| o.get(feature))
+                    .map(Vec::len)
+                    .sum::<usize>();
+                spans.len() == occurrences
+            })
+            .map(|(feature, _)| *feature)
+            .collect()
+    }
+
+    fn add(&mut self, feature: EsFeature, span: Span) {
+        if feature.is_proposal() && !self.proposals {
+            return;
+        }
+        // syntax cannot be feature tested, an unsupported engine fails to parse the whole file
+        if feature.is_built_in()
+            && self
+                .reference
+                .as_deref()
+                .is_some_and(|r| self.polyfills.covers(feature, r))
+        {
+            self.polyfilled.entry(feature).or_default().push(span);
+        }
+        let occurrences = if feature.is_built_in() && self.is_guarded() {
+            &mut self.conditional
+        } else {
+            &mut self.features
+        };
+        occurrences.entry(feature).or_default().push(span);
+    }
+
+    /// Runs `visit` for code that only runs if a feature test of the `tested` built-ins passed
+    fn visit_guarded(&mut self, tested: Vec<String>, visit: impl FnOnce(&mut Self)) {
+        let depth = self.guarded.len();
+        self.guarded.extend(tested);
+        visit(self);
+        self.guarded.truncate(depth);
+    }
+
+    /// `true` if the built-in of the current occurrence is tested by an enclosing feature test.
+    /// Occurrences without a reference, like the literal `10n`, fail to parse in older engines.
+    fn is_guarded(&self) -> bool {
+        self.reference.as_deref().is_some_and(|reference| {
+            self.guarded
+                .iter()
+                .any(|tested| tested == ANY_BUILT_IN || tests(tested, reference))
+        })
+    }
+
+    /// Built-ins that exist when a feature test like `typeof Promise.any === 'function'`,
+    /// `'fromEntries' in Object`, `window.Symbol` or a combination of them is `truthy`, or
+    /// falsy for `!Promise.any`. Empty if the condition is no feature test.
+    fn feature_tests(&self, expr: &Expr, truthy: bool) -> Vec<String> {
+        match expr {
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Bang,
+                arg,
+                ..
+            }) => self.feature_tests(arg, !truthy),
+            Expr::Bin(BinExpr {
+                op: BinaryOp::In,
+                left,
+                right,
+                ..
+            }) if truthy => {
+                let object = self.built_in_reference(right);
+                match (object, left.deref()) {
+                    (Some(object), Expr::Lit(Lit::Str(key))) => {
+                        vec![format!("{}.{}", object, key.value)]
+                    }
+                    (object, _) => object.into_iter().collect(),
+                }
+            }
+            Expr::Bin(BinExpr {
+                op: op @ (BinaryOp::EqEq | BinaryOp::NotEq | BinaryOp::EqEqEq | BinaryOp::NotEqEq),
+                left,
+                right,
+                ..
+            }) => {
+                // `typeof Promise.any === 'function'` or `typeof Symbol !== 'undefined'`
+                let (arg, kind) = match (left.deref(), right.deref()) {
+                    (
+                        Expr::Unary(UnaryExpr {
+                            op: UnaryOp::TypeOf,
+                            arg,
+                            ..
+                        }),
+                        Expr::Lit(Lit::Str(kind)),
+                    )
+                    | (
+                        Expr::Lit(Lit::Str(kind)),
+                        Expr::Unary(UnaryExpr {
+                            op: UnaryOp::TypeOf,
+                            arg,
+                            ..
+                        }),
+                    ) => (arg, kind),
+                    _ => return vec![],
+                };
+                let equal = matches!(op, BinaryOp::EqEq | BinaryOp::EqEqEq);
+                if truthy == (equal != (&kind.value == "undefined")) {
+                    self.built_in_reference(arg).into_iter().collect()
+                } else {
+                    vec![]
+                }
+            }
+            // both sides are truthy, or both are falsy
+            Expr::Bin(BinExpr {
+                op: op @ (BinaryOp::LogicalAnd | BinaryOp::LogicalOr),
+                left,
+                right,
+                ..
+            }) if truthy == (*op == BinaryOp::LogicalAnd) => {
+                let mut tested = self.feature_tests(left, truthy);
+                tested.extend(self.feature_tests(right, truthy));
+                tested
+            }
+            Expr::Paren(p) => self.feature_tests(&p.expr, truthy),
+            // `if (MyApp.debug)` or `if (Foo)` test the application, not the engine
+            Expr::Member(_) if truthy => self.built_in_reference(expr).into_iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Path of a reference to a built-in or its members, like `Promise.any` or
+    /// `Array.prototype.at`. Globals such as `window` or `process` only tell the environment.
+    fn built_in_reference(&self, expr: &Expr) -> Option<String> {
+        self.reference(expr).filter(|r| is_built_in(r))
+    }
+
+    /// Path of the global or member an expression refers to, like `Promise.any`, and
+    /// `prototype.at` for a member of an object that is no built-in
+    fn reference(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Member(m) => self.member_reference(m),
+            Expr::Paren(p) => self.reference(&p.expr),
+            _ => self.aliases.global_name(expr).or_else(|| {
+                let (object, member) = self.aliases.static_member(expr)?;
+                Some(format!("{}.{}", object, member))
+            }),
+        }
+    }
+
+    fn member_reference(&self, n: &MemberExpr) -> Option<String> {
+        let member = prop_name(&n.prop)?;
+        match self.reference(&n.obj) {
+            Some(object) if GLOBAL_OBJECTS.contains(&object.as_str()) => Some(member),
+            Some(object) if is_built_in(&object) => Some(format!("{}.{}", object, member)),
+            _ => Some(format!("prototype.{}", member)),
+        }
+    }
+
+    /// Checks function parameters for default values and rest parameters
+    fn check_params<'a>(&mut self, params: impl Iterator<Item = &'a Pat>) {
+        for param in params {
+            match param {
+                Pat::Assign(p) => self.add(EsFeature::DefaultParameters, p.span),
+                Pat::Rest(p) => self.add(EsFeature::RestParameters, p.span),
+                _ => {}
+            }
+        }
+    }
+
+    /// Looks for a `"use strict"` directive in the prologue of a script or function body
+    fn check_directives(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            let Stmt::Expr(ExprStmt { expr, span }) = stmt else {
+                break;
+            };
+            let Expr::Lit(Lit::Str(str)) = expr.deref() else {
+                break;
+            };
+            if &str.value == "use strict" {
+                self.add(EsFeature::StrictMode, *span);
+            }
+        }
+    }
+
+    /// Looks for a comma between the last parameter or argument and the closing parenthesis
+    fn check_trailing_comma(&mut self, last: Option<Span>, end: BytePos) {
+        let Some(last) = last else {
+            return;
+        };
+        let has_comma = self
+            .cm
+            .with_snippet_of_span(Span::new(last.hi, end), |mut rest| loop {
+                rest = rest.trim_start();
+                if let Some(comment) = rest.strip_prefix("//") {
+                    rest = comment.split_once('\n').map_or("", |(_, r)| r);
+                } else if let Some(comment) = rest.strip_prefix("/*") {
+                    rest = comment.split_once("*/").map_or("", |(_, r)| r);
+                } else {
+                    return rest.starts_with(',');
+                }
+            })
+            .unwrap_or(false);
+        if has_comma {
+            self.add(
+                EsFeature::TrailingCommasInFunctionParameterListsAndCalls,
+                Span::new(last.hi, end),
+            );
+        }
+    }
+
+    /// Import attributes, `type: "json"` additionally imports a JSON module
+    fn check_import_attributes(&mut self, attributes: &ObjectLit, span: Span) {
+        self.add(EsFeature::ImportAttributes, span);
+        let is_json = attributes.props.iter().any(|p| match p {
+            PropOrSpread::Prop(p) => match p.deref() {
+                Prop::KeyValue(KeyValueProp { key, value }) => {
+                    matches!(key, PropName::Ident(k) if &k.sym == "type")
+                        && matches!(value.deref(), Expr::Lit(Lit::Str(v)) if &v.value == "json")
+                }
+                _ => false,
+            },
+            PropOrSpread::Spread(_) => false,
+        });
+        if is_json {
+            self.add(EsFeature::JsonModules, span);
+        }
+    }
+
+    /// `import defer` and `import source`
+    fn check_import_phase(&mut self, phase: ImportPhase, span: Span) {
+        match phase {
+            ImportPhase::Defer => self.add(EsFeature::ImportDefer, span),
+            ImportPhase::Source => self.add(EsFeature::SourcePhaseImports, span),
+            ImportPhase::Evaluation => {}
+        }
+    }
+
+    /// Looks for a `cause` in the options of an error constructor
+    fn check_error_cause(&mut self, callee: &str, args: &[ExprOrSpread], span: Span) {
+        let options = match callee {
+            "Error" | "EvalError" | "RangeError" | "ReferenceError" | "SyntaxError"
+            | "TypeError" | "URIError" => args.get(1),
+            "AggregateError" => args.get(2),
+            _ => None,
+        };
+        let Some(Expr::Object(options)) = options.map(|o| o.expr.deref()) else {
+            return;
+        };
+        if has_key(options, "cause") {
+            self.add(EsFeature::ErrorCause, span);
+        }
+    }
+
+    /// Checks the pattern and flags of a `RegExp(pattern, flags)` call, as far as they are
+    /// string literals
+    fn check_regexp_args(&mut self, args: &[ExprOrSpread], span: Span) {
+        let literal = |arg: Option<&ExprOrSpread>| match arg.map(|a| a.expr.deref()) {
+            Some(Expr::Lit(Lit::Str(str))) if arg.is_some_and(|a| a.spread.is_none()) => {
+                Some(str.value.to_string())
+            }
+            _ => None,
+        };
+        let pattern = literal(args.first());
+        let flags = literal(args.get(1)).unwrap_or_default();
+        for feature in regex::features(pattern.as_deref(), &flags) {
+            self.add(feature, span);
+        }
     }
 }
 
 impl Visit for FeatureFinder {
+    fn visit_array_pat(&mut self, n: &ArrayPat) {
+        self.add(EsFeature::Destructuring, n.span);
+        n.visit_children_with(self)
+    }
+
     fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        self.add(EsFeature::ArrowFunctions, n.span);
+        self.check_params(n.params.iter());
+        self.check_trailing_comma(n.params.last().map(|p| p.span()), n.body.span_lo());
         let prev_in_function = self.in_function;
         self.in_function = true;
         n.visit_children_with(self);
         self.in_function = prev_in_function;
     }
 
-    fn visit_assign_op(&mut self, n: &AssignOp) {
-        if let AssignOp::ExpAssign = &n {
-            self.set.insert(EsFeature::ExponentiationOperator);
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        if let AssignOp::ExpAssign = &n.op {
+            self.add(EsFeature::ExponentiationOperator, n.span);
         }
-        if let AssignOp::NullishAssign = &n {
-            self.set.insert(EsFeature::LogicalAssignmentOperators);
+        if let AssignOp::NullishAssign = &n.op {
+            self.add(EsFeature::LogicalAssignmentOperators, n.span);
         }
-        if let AssignOp::AndAssign = &n {
-            self.set.insert(EsFeature::LogicalAssignmentOperators);
+        if let AssignOp::AndAssign = &n.op {
+            self.add(EsFeature::LogicalAssignmentOperators, n.span);
         }
-        if let AssignOp::OrAssign = &n {
-            self.set.insert(EsFeature::LogicalAssignmentOperators);
+        if let AssignOp::OrAssign = &n.op {
+            self.add(EsFeature::LogicalAssignmentOperators, n.span);
         }
         n.visit_children_with(self)
     }
@@ -59,10 +453,11 @@ impl Visit for FeatureFinder {
     fn visit_await_expr(&mut self, n: &AwaitExpr) {
         if !self.in_function {
             // wait on top level
-            self.set.insert(EsFeature::TopLevelAwait);
+            self.add(EsFeature::TopLevelAwait, n.span);
+            self.module_syntax = true;
         } else {
             // wait inside a function
-            self.set.insert(EsFeature::AsyncFunctions);
+            self.add(EsFeature::AsyncFunctions, n.span);
         }
         n.visit_children_with(self)
     }
@@ -71,165 +466,470 @@ impl Visit for FeatureFinder {
         if let Expr::PrivateName(_) = n.left.deref() {
             // Check for the 'in' operator which is commonly used for brand checks
             if let BinaryOp::In = &n.op {
-                self.set
-                    .insert(EsFeature::ErgonomicBrandChecksForPrivateFields);
+                self.add(EsFeature::ErgonomicBrandChecksForPrivateFields, n.span);
             }
         }
-        n.visit_children_with(self)
-    }
-
-    fn visit_binary_op(&mut self, n: &BinaryOp) {
-        if let BinaryOp::Exp = &n {
-            self.set.insert(EsFeature::ExponentiationOperator);
+        if let BinaryOp::Exp = &n.op {
+            self.add(EsFeature::ExponentiationOperator, n.span);
         }
-        if let BinaryOp::NullishCoalescing = &n {
-            self.set.insert(EsFeature::NullishCoalescingOperator);
+        if let BinaryOp::NullishCoalescing = &n.op {
+            self.add(EsFeature::NullishCoalescingOperator, n.span);
+        }
+        match n.op {
+            // `typeof Symbol === 'function' && Symbol.asyncIterator`
+            BinaryOp::LogicalAnd => {
+                n.left.visit_with(self);
+                let tested = self.feature_tests(&n.left, true);
+                self.visit_guarded(tested, |v| n.right.visit_with(v))
+            }
+            // `Symbol.asyncIterator || '@@asyncIterator'`, `!Promise.any || Promise.any(xs)`
+            BinaryOp::LogicalOr | BinaryOp::NullishCoalescing => {
+                let tested = self.built_in_reference(&n.left).into_iter().collect();
+                self.visit_guarded(tested, |v| n.left.visit_with(v));
+                let tested = match n.op {
+                    BinaryOp::LogicalOr => self.feature_tests(&n.left, false),
+                    _ => vec![],
+                };
+                self.visit_guarded(tested, |v| n.right.visit_with(v));
+            }
+            _ => n.visit_children_with(self),
         }
-
-        n.visit_children_with(self)
     }
 
     fn visit_call_expr(&mut self, n: &CallExpr) {
+        if let Callee::Expr(_) | Callee::Super(_) = &n.callee {
+            self.check_trailing_comma(n.args.last().map(|a| a.span()), n.span.hi);
+        }
+        self.reference = match &n.callee {
+            Callee::Expr(e) => self.reference(e),
+            _ => None,
+        };
         if let Callee::Expr(e) = &n.callee {
-            if let Expr::Member(m) = e.deref() {
-                if let Expr::Ident(a) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if let ("values" | "entries", "Object") = (i.sym.deref(), a.sym.deref()) {
-                            self.set.insert(EsFeature::ObjectValuesEntries);
-                        }
-                    }
+            if let Some((a, i)) = self.aliases.static_member(e) {
+                if let (
+                    "keys"
+                    | "create"
+                    | "defineProperty"
+                    | "defineProperties"
+                    | "getPrototypeOf"
+                    | "getOwnPropertyNames"
+                    | "getOwnPropertyDescriptor"
+                    | "freeze"
+                    | "isFrozen"
+                    | "seal"
+                    | "isSealed"
+                    | "preventExtensions"
+                    | "isExtensible",
+                    "Object",
+                )
+                | ("isArray", "Array")
+                | ("now", "Date")
+                | ("parse" | "stringify", "JSON") = (i.as_str(), a.as_str())
+                {
+                    self.add(EsFeature::Es5BuiltIns, n.span);
                 }
 
-                if let Expr::Array(_) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if let "groupBy" = i.sym.deref() {
-                            self.set.insert(EsFeature::ArrayGrouping);
-                        }
-                    }
+                if let ("assign" | "is" | "setPrototypeOf" | "getOwnPropertySymbols", "Object")
+                | ("from" | "of", "Array")
+                | (
+                    "isInteger" | "isSafeInteger" | "isFinite" | "isNaN" | "parseFloat"
+                    | "parseInt",
+                    "Number",
+                )
+                | ("raw" | "fromCodePoint", "String")
+                | (
+                    "trunc" | "sign" | "cbrt" | "log10" | "log2" | "log1p" | "expm1" | "hypot"
+                    | "imul" | "clz32" | "fround" | "sinh" | "cosh" | "tanh" | "asinh" | "acosh"
+                    | "atanh",
+                    "Math",
+                ) = (i.as_str(), a.as_str())
+                {
+                    self.add(EsFeature::Es2015BuiltIns, n.span);
                 }
 
-                if let Expr::Ident(a) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if let ("groupBy", "Object" | "Map") = (i.sym.deref(), a.sym.deref()) {
-                            self.set.insert(EsFeature::ArrayGrouping);
-                        }
-                    }
+                if let ("resolve" | "reject" | "all" | "race", "Promise") = (i.as_str(), a.as_str())
+                {
+                    self.add(EsFeature::Promises, n.span);
+                }
+                if a == "Proxy" && i == "revocable" {
+                    self.add(EsFeature::Proxies, n.span);
+                }
+                if TYPED_ARRAYS.contains(&a.as_str())
+                    && matches!(i.as_str(), "from" | "of" | "isView")
+                {
+                    self.add(EsFeature::TypedArrays, n.span);
                 }
 
-                if let Expr::Ident(a) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if &i.sym == "getOwnPropertyDescriptors" && &a.sym == "Object" {
-                            self.set.insert(EsFeature::ObjectGetOwnPropertyDescriptors);
-                        }
-                    }
+                if let ("values" | "entries", "Object") = (i.as_str(), a.as_str()) {
+                    self.add(EsFeature::ObjectValuesEntries, n.span);
                 }
 
-                if let Expr::Ident(a) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if &i.sym == "hasOwn" && &a.sym == "Object" {
-                            self.set
-                                .insert(EsFeature::AccessibleObjectPrototypeHasOwnProperty);
-                        }
+                if i == "fromEntries" && a == "Object" {
+                    self.add(EsFeature::ObjectFromEntries, n.span);
+                }
+
+                if let ("groupBy", "Object" | "Map") = (i.as_str(), a.as_str()) {
+                    self.add(EsFeature::ArrayGrouping, n.span);
+                }
+
+                if i == "getOwnPropertyDescriptors" && a == "Object" {
+                    self.add(EsFeature::ObjectGetOwnPropertyDescriptors, n.span);
+                }
+
+                if i == "hasOwn" && a == "Object" {
+                    self.add(EsFeature::AccessibleObjectPrototypeHasOwnProperty, n.span);
+                }
+
+                if a == "Promise" {
+                    if i == "allSettled" {
+                        self.add(EsFeature::PromiseAllSettled, n.span);
+                    }
+                    if i == "any" {
+                        self.add(EsFeature::PromiseAny, n.span);
+                    }
+                    if i == "withResolvers" {
+                        self.add(EsFeature::PromiseWithResolvers, n.span);
                     }
                 }
 
-                if let Expr::Ident(_) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if &i.sym == "fromEntries" {
-                            self.set.insert(EsFeature::ObjectFromEntries);
-                        }
+                if a == "Atomics" && i == "waitAsync" {
+                    self.add(EsFeature::AtomicsWaitAsync, n.span);
+                }
+
+                match (i.as_str(), a.as_str()) {
+                    ("from", "Iterator") => self.add(EsFeature::IteratorHelpers, n.span),
+                    ("escape", "RegExp") => self.add(EsFeature::RegExpEscaping, n.span),
+                    ("from" | "of", "Float16Array") | ("f16round", "Math") => {
+                        self.add(EsFeature::Float16Array, n.span)
+                    }
+                    ("try", "Promise") => self.add(EsFeature::PromiseTry, n.span),
+                    ("fromAsync", "Array") => self.add(EsFeature::ArrayFromAsync, n.span),
+                    ("isError", "Error") => self.add(EsFeature::ErrorIsError, n.span),
+                    ("sumPrecise", "Math") => self.add(EsFeature::MathSumPrecise, n.span),
+                    ("fromBase64" | "fromHex", "Uint8Array") => {
+                        self.add(EsFeature::Uint8ArrayBase64, n.span)
                     }
+                    _ => {}
                 }
+            }
 
-                if let Expr::Ident(a) = &m.obj.deref() {
+            if let Expr::Member(m) = e.deref() {
+                if let Expr::Array(_) = &m.obj.deref() {
                     if let MemberProp::Ident(i) = &m.prop {
-                        if &a.sym == "Promise" {
-                            if &i.sym == "allSettled" {
-                                self.set.insert(EsFeature::PromiseAllSettled);
-                            }
-                            if &i.sym == "any" {
-                                self.set.insert(EsFeature::PromiseAny);
-                            }
-                            if &i.sym == "withResolvers" {
-                                self.set.insert(EsFeature::PromiseWithResolvers);
-                            }
+                        if let "groupBy" = i.sym.deref() {
+                            self.add(EsFeature::ArrayGrouping, n.span);
                         }
                     }
                 }
 
-                if let Expr::Ident(a) = &m.obj.deref() {
-                    if let MemberProp::Ident(i) = &m.prop {
-                        if &a.sym == "Atomics" && &i.sym == "waitAsync" {
-                            self.set.insert(EsFeature::AtomicsWaitAsync);
+                // prototype methods, recognized by name unless the receiver was created with
+                // a literal or constructor in this file
+                if let Some(i) = prop_name(&m.prop) {
+                    match i.as_str() {
+                        "drop" | "take" | "toArray" | "map" | "filter" | "flatMap" | "reduce"
+                        | "forEach" | "some" | "every" | "find"
+                            if is_iterator(&m.obj, &self.aliases) =>
+                        {
+                            self.add(EsFeature::IteratorHelpers, n.span)
                         }
+                        // String.prototype.includes is ES2015, `s.includes('x')` on a string of
+                        // unknown origin still counts
+                        "includes" if may_be(&m.obj, &self.aliases, &["Array"]) => {
+                            self.add(EsFeature::ArrayPrototypeIncludes, n.span)
+                        }
+                        "padStart" | "padEnd" => self.add(EsFeature::StringPadding, n.span),
+                        "finally" if may_be(&m.obj, &self.aliases, &["Promise"]) => {
+                            self.add(EsFeature::PromisePrototypeFinally, n.span)
+                        }
+                        "trimStart" | "trimEnd" => {
+                            self.add(EsFeature::StringPrototypeTrimStartTrimEnd, n.span)
+                        }
+                        "flat" | "flatMap" => {
+                            self.add(EsFeature::ArrayPrototypeFlatFlatMap, n.span)
+                        }
+                        "matchAll" => self.add(EsFeature::StringPrototypeMatchAll, n.span),
+                        "replaceAll" => self.add(EsFeature::StringPrototypeReplaceAll, n.span),
+                        "at" if may_be(&m.obj, &self.aliases, &["Array", "String"]) => {
+                            self.add(EsFeature::At, n.span)
+                        }
+                        "findLast" | "findLastIndex" => {
+                            self.add(EsFeature::ArrayFindFromLast, n.span)
+                        }
+                        "toReversed" | "toSorted" | "toSpliced" => {
+                            self.add(EsFeature::ChangeArrayByCopy, n.span)
+                        }
+                        // `arr.with(0, x)`, query builders and date libraries have a `with` too
+                        "with"
+                            if n.args.len() == 2
+                                && is_number(&n.args[0].expr)
+                                && may_be(&m.obj, &self.aliases, &["Array"]) =>
+                        {
+                            self.add(EsFeature::ChangeArrayByCopy, n.span)
+                        }
+                        "isWellFormed" | "toWellFormed" => {
+                            self.add(EsFeature::WellFormedUnicodeStrings, n.span)
+                        }
+                        "resize" | "grow" if is_array_buffer(&m.obj, &self.aliases) => {
+                            self.add(EsFeature::ResizableAndGrowableArrayBuffers, n.span)
+                        }
+                        "transferToFixedLength" => self.add(EsFeature::ArrayBufferTransfer, n.span),
+                        "transfer" if is_array_buffer(&m.obj, &self.aliases) => {
+                            self.add(EsFeature::ArrayBufferTransfer, n.span)
+                        }
+                        "symmetricDifference"
+                        | "isSubsetOf"
+                        | "isSupersetOf"
+                        | "isDisjointFrom" => self.add(EsFeature::NetSetMethods, n.span),
+                        // common names, only on a receiver known to be a Set
+                        "union" | "intersection" | "difference"
+                            if self.aliases.instance_of(&m.obj).as_deref() == Some("Set") =>
+                        {
+                            self.add(EsFeature::NetSetMethods, n.span)
+                        }
+                        "getFloat16" | "setFloat16" => self.add(EsFeature::Float16Array, n.span),
+                        "toBase64" | "toHex" | "setFromBase64" | "setFromHex" => {
+                            self.add(EsFeature::Uint8ArrayBase64, n.span)
+                        }
+                        // only adding a symbol throws in older engines, looking one up does not
+                        "set" | "add" | "register"
+                            if is_weak_collection(&m.obj, &self.aliases)
+                                && n.args
+                                    .first()
+                                    .is_some_and(|a| is_symbol(&a.expr, &self.aliases)) =>
+                        {
+                            self.add(EsFeature::SymbolsAsWeakMapKeys, n.span)
+                        }
+                        _ => {}
                     }
                 }
             }
-            if let Expr::Ident(a) = e.deref() {
-                if &a.sym == "BigInt" {
+            if let Some(a) = self.aliases.global_name(e) {
+                if a == "Symbol" {
+                    self.add(EsFeature::Symbols, n.span);
+                }
+                if a == "BigInt" {
                     if let Some(args) = n.args.first() {
                         if let Expr::Lit(Lit::Str(_)) = &args.expr.deref() {
-                            self.set.insert(EsFeature::BigInt);
+                            self.add(EsFeature::BigInt, n.span);
                         } else if let Expr::Lit(Lit::Num(_)) = &args.expr.deref() {
-                            self.set.insert(EsFeature::BigInt);
+                            self.add(EsFeature::BigInt, n.span);
                         }
                     }
                 }
+                if a == "RegExp" {
+                    self.check_regexp_args(&n.args, n.span);
+                }
+                self.check_error_cause(&a, &n.args, n.span);
             }
         }
+        if let Callee::Import(import) = &n.callee {
+            self.add(EsFeature::Import, n.span);
+            self.check_import_phase(import.phase, n.span);
+            if let Some(Expr::Object(options)) = n.args.get(1).map(|a| a.expr.deref()) {
+                let attributes = options.props.iter().find_map(|p| match p {
+                    PropOrSpread::Prop(p) => match p.deref() {
+                        Prop::KeyValue(KeyValueProp {
+                            key: PropName::Ident(key),
+                            value,
+                        }) if &key.sym == "with" => match value.deref() {
+                            Expr::Object(attributes) => Some(attributes),
+                            _ => None,
+                        },
+                        _ => None,
+                    },
+                    PropOrSpread::Spread(_) => None,
+                });
+                if let Some(attributes) = attributes {
+                    self.check_import_attributes(attributes, n.span);
+                }
+            }
+        }
+        self.reference = None;
         n.visit_children_with(self)
     }
 
+    fn visit_class(&mut self, n: &Class) {
+        self.add(EsFeature::Classes, n.span);
+        n.visit_children_with(self)
+    }
+
+    fn visit_class_decl(&mut self, n: &ClassDecl) {
+        // `declare class` has no runtime code
+        if !n.declare {
+            n.visit_children_with(self)
+        }
+    }
+
     fn visit_class_member(&mut self, n: &ClassMember) {
         match n {
+            // TypeScript declarations without runtime code
+            ClassMember::ClassProp(prop) if prop.declare || prop.is_abstract => return,
+            // `x: number;` only declares a type
+            ClassMember::ClassProp(ClassProp {
+                value: None,
+                type_ann: Some(_),
+                ..
+            })
+            | ClassMember::PrivateProp(PrivateProp {
+                value: None,
+                type_ann: Some(_),
+                ..
+            }) if self.typescript => {}
+            ClassMember::TsIndexSignature(_) => return,
             ClassMember::PrivateMethod(_)
             | ClassMember::PrivateProp(_)
             | ClassMember::ClassProp(_) => {
-                self.set.insert(EsFeature::ClassFields);
+                self.add(EsFeature::ClassFields, n.span());
             }
-            ClassMember::Method(method) if method.is_static => {
-                self.set.insert(EsFeature::ClassFields);
+            // `accessor` fields come with the decorators proposal
+            ClassMember::AutoAccessor(_) => {
+                self.add(EsFeature::ClassFields, n.span());
+                if !self.typescript {
+                    self.add(EsFeature::Decorators, n.span());
+                }
             }
             _ => {}
         }
         n.visit_children_with(self)
     }
 
+    fn visit_cond_expr(&mut self, n: &CondExpr) {
+        n.test.visit_with(self);
+        let tested = self.feature_tests(&n.test, true);
+        self.visit_guarded(tested, |v| n.cons.visit_with(v));
+        let tested = self.feature_tests(&n.test, false);
+        self.visit_guarded(tested, |v| n.alt.visit_with(v));
+    }
+
+    fn visit_constructor(&mut self, n: &Constructor) {
+        self.check_params(n.params.iter().filter_map(|p| match p {
+            ParamOrTsParamProp::Param(p) => Some(&p.pat),
+            // parameter properties compile to assignments in the constructor,
+            // only their default values remain
+            ParamOrTsParamProp::TsParamProp(_) => None,
+        }));
+        self.check_trailing_comma(
+            n.params.last().map(|p| p.span()),
+            n.body.as_ref().map_or(n.span.hi, |b| b.span.lo),
+        );
+        for param in &n.params {
+            if let ParamOrTsParamProp::TsParamProp(TsParamProp {
+                param: TsParamPropParam::Assign(p),
+                ..
+            }) = param
+            {
+                self.add(EsFeature::DefaultParameters, p.span);
+            }
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_decorator(&mut self, n: &Decorator) {
+        if !self.typescript {
+            self.add(EsFeature::Decorators, n.span);
+        }
+        n.visit_children_with(self)
+    }
+
     fn visit_expr(&mut self, n: &Expr) {
         if let Expr::OptChain(_) = n {
-            self.set.insert(EsFeature::OptionalChaining);
+            self.add(EsFeature::OptionalChaining, n.span());
         }
 
         n.visit_children_with(self)
     }
 
+    fn visit_expr_or_spread(&mut self, n: &ExprOrSpread) {
+        if let Some(span) = n.spread {
+            self.add(EsFeature::SpreadOperator, span);
+        }
+        n.visit_children_with(self)
+    }
+
     fn visit_fn_decl(&mut self, n: &FnDecl) {
+        if n.declare {
+            return;
+        }
         if n.function.is_async {
-            self.set.insert(EsFeature::AsyncFunctions);
+            self.add(EsFeature::AsyncFunctions, n.function.span);
         }
         n.visit_children_with(self)
     }
 
     fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
-        self.set.insert(EsFeature::ForInMechanics);
+        self.add(EsFeature::ForInMechanics, n.span);
+        n.visit_children_with(self)
+    }
+
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+        self.add(EsFeature::ForOf, n.span);
+        if n.is_await {
+            self.add(EsFeature::AsynchronousIteration, n.span);
+        }
         n.visit_children_with(self)
     }
 
     fn visit_function(&mut self, n: &Function) {
+        // overload signatures and abstract methods have no runtime code
+        if n.body.is_none() {
+            return;
+        }
+        if n.is_generator {
+            self.add(EsFeature::Generators, n.span);
+        }
+        if n.is_generator && n.is_async {
+            self.add(EsFeature::AsynchronousIteration, n.span);
+        }
+        self.check_params(n.params.iter().map(|p| &p.pat));
+        if let Some(body) = &n.body {
+            self.check_trailing_comma(n.params.last().map(|p| p.span), body.span.lo);
+        }
+        if let Some(body) = &n.body {
+            self.check_directives(&body.stmts);
+        }
         let prev_in_function = self.in_function;
         self.in_function = true;
         n.visit_children_with(self);
         self.in_function = prev_in_function;
     }
 
+    fn visit_getter_prop(&mut self, n: &GetterProp) {
+        self.add(EsFeature::PropertyAccessors, n.span);
+        n.visit_children_with(self)
+    }
+
+    fn visit_if_stmt(&mut self, n: &IfStmt) {
+        n.test.visit_with(self);
+        let tested = self.feature_tests(&n.test, true);
+        self.visit_guarded(tested, |v| n.cons.visit_with(v));
+        let tested = self.feature_tests(&n.test, false);
+        self.visit_guarded(tested, |v| n.alt.visit_with(v));
+    }
+
+    fn visit_jsx_attr_or_spread(&mut self, n: &JSXAttrOrSpread) {
+        match n {
+            // spread attributes are JSX syntax, not object spread
+            JSXAttrOrSpread::SpreadElement(spread) => spread.expr.visit_with(self),
+            JSXAttrOrSpread::JSXAttr(attr) => attr.visit_with(self),
+        }
+    }
+
     fn visit_lit(&mut self, n: &Lit) {
         if let Lit::BigInt(_) = n {
-            self.set.insert(EsFeature::BigInt);
+            self.add(EsFeature::BigIntLiterals, n.span());
+        }
+        if let Lit::Str(str) = n {
+            // escaped line and paragraph separators were always allowed
+            if let Some(raw) = &str.raw {
+                if raw.contains(['\u{2028}', '\u{2029}']) {
+                    self.add(EsFeature::JSONSuperset, n.span());
+                }
+            }
         }
         if let Lit::Num(i) = n {
             if let Some(v) = &i.raw {
                 if v.contains("_") {
-                    self.set.insert(EsFeature::NumericSeparators);
+                    self.add(EsFeature::NumericSeparators, n.span());
                 }
             }
         }
@@ -237,97 +937,511 @@ impl Visit for FeatureFinder {
     }
 
     fn visit_member_expr(&mut self, n: &MemberExpr) {
-        if let Expr::Ident(m) = n.obj.deref() {
-            if &m.sym == "globalThis" {
-                self.set.insert(EsFeature::GlobalThis);
+        self.reference = self.member_reference(n);
+        if let Some(m) = self.aliases.global_name(&n.obj) {
+            if m == "globalThis" {
+                self.add(EsFeature::GlobalThis, n.span);
+            }
+            if m == "Symbol" {
+                self.add(EsFeature::Symbols, n.span);
+                if let Some(i) = prop_name(&n.prop) {
+                    if i == "asyncIterator" {
+                        self.add(EsFeature::SymbolAsyncIterator, n.span);
+                    }
+                    if let "dispose" | "asyncDispose" = i.as_str() {
+                        self.add(EsFeature::ExplicitResourceManagementBuiltIns, n.span);
+                    }
+                }
+            }
+            if m == "Reflect" {
+                self.add(EsFeature::Reflect, n.span);
+            }
+            if m == "Temporal" {
+                self.add(EsFeature::Temporal, n.span);
+            }
+            if m == "AsyncContext" {
+                self.add(EsFeature::AsyncContext, n.span);
+            }
+            if let Some(i) = prop_name(&n.prop) {
+                if let ("Number", "EPSILON" | "MAX_SAFE_INTEGER" | "MIN_SAFE_INTEGER") =
+                    (m.as_str(), i.as_str())
+                {
+                    self.add(EsFeature::Es2015BuiltIns, n.span);
+                }
+            }
+        }
+        if let Some(i) = prop_name(&n.prop) {
+            if i == "description" && is_symbol_like(&n.obj, &self.aliases) {
+                self.add(EsFeature::SymbolPrototypeDescription, n.span);
+            }
+            if i == "detached" && is_array_buffer(&n.obj, &self.aliases) {
+                self.add(EsFeature::ArrayBufferTransfer, n.span);
+            }
+            if i == "prototype"
+                && self
+                    .aliases
+                    .global_name(&n.obj)
+                    .is_some_and(|o| o == "Iterator")
+            {
+                self.add(EsFeature::IteratorHelpers, n.span);
             }
         }
+        self.reference = None;
+        n.visit_children_with(self)
+    }
+
+    fn visit_meta_prop_expr(&mut self, n: &MetaPropExpr) {
+        if let MetaPropKind::NewTarget = n.kind {
+            self.add(EsFeature::NewTarget, n.span);
+        }
+        if let MetaPropKind::ImportMeta = n.kind {
+            self.add(EsFeature::ImportMeta, n.span);
+            self.module_syntax = true;
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_module(&mut self, n: &Module) {
+        if n.shebang.is_some() {
+            self.add(EsFeature::HashbangGrammar, n.span);
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_module_decl(&mut self, n: &ModuleDecl) {
+        match n {
+            // compiled to CommonJS by TypeScript
+            ModuleDecl::TsImportEquals(_)
+            | ModuleDecl::TsExportAssignment(_)
+            | ModuleDecl::TsNamespaceExport(_) => return n.visit_children_with(self),
+            // erased by TypeScript
+            ModuleDecl::Import(ImportDecl {
+                type_only: true, ..
+            })
+            | ModuleDecl::ExportNamed(NamedExport {
+                type_only: true, ..
+            }) => return,
+            ModuleDecl::ExportDecl(ExportDecl { decl, .. }) if is_type_only(decl) => return,
+            _ => {}
+        }
+        self.add(EsFeature::Modules, n.span());
+        self.module_syntax = true;
+        if let ModuleDecl::Import(import) = n {
+            self.check_import_phase(import.phase, n.span());
+        }
+        let attributes = match n {
+            ModuleDecl::Import(ImportDecl { with, .. })
+            | ModuleDecl::ExportNamed(NamedExport { with, .. })
+            | ModuleDecl::ExportAll(ExportAll { with, .. }) => with.as_deref(),
+            _ => None,
+        };
+        if let Some(attributes) = attributes {
+            self.check_import_attributes(attributes, n.span());
+        }
         n.visit_children_with(self)
     }
 
     fn visit_new_expr(&mut self, n: &NewExpr) {
-        if let Expr::Ident(i) = n.callee.deref() {
-            if &i.sym == "SharedArrayBuffer" {
-                self.set.insert(EsFeature::SharedMemoryAndAtomics);
-            } else if &i.sym == "RegExp" {
+        if let Some(args) = &n.args {
+            self.check_trailing_comma(args.last().map(|a| a.span()), n.span.hi);
+        }
+        self.reference = self.reference(&n.callee);
+        if let Some(i) = self.aliases.global_name(&n.callee) {
+            if i == "SharedArrayBuffer" {
+                self.add(EsFeature::SharedMemoryAndAtomics, n.span);
+            } else if let "Map" | "Set" | "WeakMap" | "WeakSet" = i.as_str() {
+                self.add(EsFeature::KeyedCollections, n.span);
+            } else if i == "Proxy" {
+                self.add(EsFeature::Proxies, n.span);
+            } else if i == "Promise" {
+                self.add(EsFeature::Promises, n.span);
+            } else if TYPED_ARRAYS.contains(&i.as_str()) {
+                self.add(EsFeature::TypedArrays, n.span);
+            } else if i == "RegExp" {
                 if let Some(args) = &n.args {
-                    if let Some(flags) = args.get(1) {
-                        if let Expr::Lit(Lit::Str(str)) = flags.expr.deref() {
-                            if str.value.contains("s") {
-                                self.set.insert(EsFeature::SDotAllFlagForRegularExpressions);
-                            }
-                            if str.value.contains("d") {
-                                self.set.insert(EsFeature::RegExpMatchIndices);
-                            }
-                            if str.value.contains("v") {
-                                self.set.insert(
-                                    EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings,
-                                );
-                            }
-                        }
+                    self.check_regexp_args(args, n.span);
+                }
+            } else if let "WeakRef" | "FinalizationRegistry" = i.as_str() {
+                self.add(EsFeature::WeakRefs, n.span);
+            } else if i == "Float16Array" {
+                self.add(EsFeature::Float16Array, n.span);
+            } else if let "DisposableStack" | "AsyncDisposableStack" = i.as_str() {
+                self.add(EsFeature::ExplicitResourceManagementBuiltIns, n.span);
+            } else if i == "ShadowRealm" {
+                self.add(EsFeature::ShadowRealm, n.span);
+            }
+            if let ("ArrayBuffer" | "SharedArrayBuffer", Some(args)) = (i.as_str(), &n.args) {
+                if let Some(Expr::Object(options)) = args.get(1).map(|a| a.expr.deref()) {
+                    if has_key(options, "maxByteLength") {
+                        self.add(EsFeature::ResizableAndGrowableArrayBuffers, n.span);
                     }
-
-                    if let Some(flags) = args.first() {
-                        if let Expr::Lit(Lit::Str(str)) = flags.expr.deref() {
-                            if str.value.contains("(?<=") || str.value.contains("(?<!") {
-                                self.set.insert(EsFeature::RegExpLookbehindAssertions);
-                            }
-                            if str.value.contains("\\p{") || str.value.contains("\\P{") {
-                                if let Expr::Lit(Lit::Str(str)) = flags.expr.deref() {
-                                    if str.value.contains("u") {
-                                        self.set.insert(EsFeature::RegExpUnicodePropertyEscapes);
-                                    }
-                                }
-                            }
-                        }
+                }
+            }
+            if let Some(args) = &n.args {
+                self.check_error_cause(&i, args, n.span);
+                if let Some(first) = args.first() {
+                    let symbol_keys =
+                        match (i.as_str(), first.expr.deref()) {
+                            ("WeakRef", arg) => is_symbol(arg, &self.aliases),
+                            ("WeakSet", Expr::Array(values)) => values
+                                .elems
+                                .iter()
+                                .flatten()
+                                .any(|v| is_symbol(&v.expr, &self.aliases)),
+                            ("WeakMap", Expr::Array(entries)) => entries
+                                .elems
+                                .iter()
+                                .flatten()
+                                .any(|e| match e.expr.deref() {
+                                    Expr::Array(entry) => entry
+                                        .elems
+                                        .first()
+                                        .and_then(|k| k.as_ref())
+                                        .is_some_and(|k| is_symbol(&k.expr, &self.aliases)),
+                                    _ => false,
+                                }),
+                            _ => false,
+                        };
+                    if symbol_keys {
+                        self.add(EsFeature::SymbolsAsWeakMapKeys, n.span);
                     }
                 }
             }
         }
+        self.reference = None;
         n.visit_children_with(self)
     }
 
-    fn visit_regex(&mut self, n: &Regex) {
-        if n.flags.contains("s") {
-            self.set.insert(EsFeature::SDotAllFlagForRegularExpressions);
+    fn visit_object_pat(&mut self, n: &ObjectPat) {
+        self.add(EsFeature::Destructuring, n.span);
+        for prop in &n.props {
+            if let ObjectPatProp::Rest(rest) = prop {
+                self.add(EsFeature::RestSpreadProperties, rest.span);
+            }
         }
-        if n.flags.contains("d") {
-            self.set.insert(EsFeature::RegExpMatchIndices);
+        n.visit_children_with(self)
+    }
+
+    fn visit_opt_call(&mut self, n: &OptCall) {
+        self.check_trailing_comma(n.args.last().map(|a| a.span()), n.span.hi);
+        n.visit_children_with(self)
+    }
+
+    fn visit_prop(&mut self, n: &Prop) {
+        if let Prop::Shorthand(_) | Prop::Method(_) = n {
+            self.add(EsFeature::ShorthandProperties, n.span());
         }
-        if n.flags.contains("v") {
-            self.set
-                .insert(EsFeature::RegexpVFlagWithSetNotationAndPropertiesOfStrings);
+        n.visit_children_with(self)
+    }
+
+    fn visit_prop_name(&mut self, n: &PropName) {
+        // keys of object literals, classes and object patterns, not `a[i]`
+        if let PropName::Computed(computed) = n {
+            self.add(EsFeature::ComputedProperties, computed.span);
         }
-        if n.exp.contains("(?<=") || n.exp.contains("(?<!") {
-            self.set.insert(EsFeature::RegExpLookbehindAssertions);
+        n.visit_children_with(self)
+    }
+
+    fn visit_regex(&mut self, n: &Regex) {
+        for feature in regex::features(Some(&n.exp), &n.flags) {
+            self.add(feature, n.span);
         }
-        if (n.exp.contains("\\p{") || n.exp.contains("\\P{")) && n.flags.contains("u") {
-            self.set.insert(EsFeature::RegExpUnicodePropertyEscapes);
+        n.visit_children_with(self)
+    }
+
+    fn visit_script(&mut self, n: &Script) {
+        if n.shebang.is_some() {
+            self.add(EsFeature::HashbangGrammar, n.span);
         }
+        self.check_directives(&n.body);
         n.visit_children_with(self)
     }
 
-    fn visit_rest_pat(&mut self, n: &RestPat) {
-        self.set.insert(EsFeature::RestSpreadProperties);
+    fn visit_setter_prop(&mut self, n: &SetterProp) {
+        self.add(EsFeature::PropertyAccessors, n.span);
         n.visit_children_with(self)
     }
 
     fn visit_spread_element(&mut self, n: &SpreadElement) {
-        self.set.insert(EsFeature::RestSpreadProperties);
+        self.add(EsFeature::RestSpreadProperties, n.span());
         n.visit_children_with(self)
     }
 
     fn visit_static_block(&mut self, n: &StaticBlock) {
-        self.set.insert(EsFeature::ClassStaticBlock);
+        self.add(EsFeature::ClassStaticBlock, n.span);
+        n.visit_children_with(self)
+    }
+
+    fn visit_super(&mut self, n: &Super) {
+        self.add(EsFeature::Classes, n.span);
+        n.visit_children_with(self)
+    }
+
+    fn visit_tagged_tpl(&mut self, n: &TaggedTpl) {
+        self.add(EsFeature::TaggedTemplateLiterals, n.span);
+        // invalid escapes have no cooked value, they are only allowed in tagged templates
+        if n.tpl.quasis.iter().any(|q| q.cooked.is_none()) {
+            self.add(EsFeature::LiftingTemplateLiteralRestriction, n.span);
+        }
+        n.visit_children_with(self)
+    }
+
+    fn visit_tpl(&mut self, n: &Tpl) {
+        self.add(EsFeature::TemplateLiterals, n.span);
         n.visit_children_with(self)
     }
 
     fn visit_try_stmt(&mut self, n: &TryStmt) {
         if let Some(handler) = &n.handler {
             if handler.param.is_none() {
-                self.set.insert(EsFeature::OptionalCatchBinding);
+                self.add(EsFeature::OptionalCatchBinding, handler.span);
+            }
+        }
+        // `try { new Function('async () => {}') } catch {}` tests for syntax support
+        if evaluates_code(&n.block, &self.aliases) {
+            self.visit_guarded(vec![ANY_BUILT_IN.to_string()], |v| n.block.visit_with(v));
+            n.handler.visit_with(self);
+            n.finalizer.visit_with(self);
+        } else {
+            n.visit_children_with(self)
+        }
+    }
+
+    // TypeScript types and declarations are erased, they never require a runtime feature
+    fn visit_ts_enum_decl(&mut self, n: &TsEnumDecl) {
+        // enums compile to ES3 code, only the member initializers remain
+        if !n.declare {
+            n.visit_children_with(self)
+        }
+    }
+
+    fn visit_ts_interface_decl(&mut self, _: &TsInterfaceDecl) {}
+
+    fn visit_ts_module_block(&mut self, n: &TsModuleBlock) {
+        for item in &n.body {
+            match item {
+                // exports of a namespace are properties of the namespace object
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => {
+                    export.decl.visit_with(self)
+                }
+                item => item.visit_with(self),
             }
         }
+    }
+
+    fn visit_ts_module_decl(&mut self, n: &TsModuleDecl) {
+        // namespaces compile to ES3 code, `declare module` has no runtime code
+        if !n.declare {
+            n.visit_children_with(self)
+        }
+    }
+
+    fn visit_ts_type(&mut self, _: &TsType) {}
+
+    fn visit_ts_type_alias_decl(&mut self, _: &TsTypeAliasDecl) {}
+
+    fn visit_ts_type_ann(&mut self, _: &TsTypeAnn) {}
+
+    fn visit_ts_type_param_decl(&mut self, _: &TsTypeParamDecl) {}
+
+    fn visit_ts_type_param_instantiation(&mut self, _: &TsTypeParamInstantiation) {}
+
+    fn visit_unary_expr(&mut self, n: &UnaryExpr) {
+        if let UnaryOp::TypeOf = n.op {
+            let tested = self.reference(&n.arg).into_iter().collect();
+            self.visit_guarded(tested, |v| n.visit_children_with(v))
+        } else {
+            n.visit_children_with(self)
+        }
+    }
+
+    fn visit_using_decl(&mut self, n: &UsingDecl) {
+        self.add(EsFeature::ExplicitResourceManagement, n.span);
         n.visit_children_with(self)
     }
+
+    fn visit_var_decl(&mut self, n: &VarDecl) {
+        if n.declare {
+            return;
+        }
+        if let VarDeclKind::Let | VarDeclKind::Const = n.kind {
+            self.add(EsFeature::LetConst, n.span);
+        }
+        n.visit_children_with(self)
+    }
+}
+
+/// `true` if a path like `Promise.any` starts at a standard global
+fn is_built_in(reference: &str) -> bool {
+    let global = reference.split('.').next().unwrap_or_default();
+    BUILT_INS.contains(&global) || TYPED_ARRAYS.contains(&global)
+}
+
+/// `true` if a feature test of `tested` ensures `reference` exists, `Promise` tests
+/// `Promise.any` and the other way around, `Array.prototype.at` tests `prototype.at`
+fn tests(tested: &str, reference: &str) -> bool {
+    let (shorter, longer) = if tested.len() <= reference.len() {
+        (tested, reference)
+    } else {
+        (reference, tested)
+    };
+    longer
+        .strip_prefix(shorter)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        || (reference.starts_with("prototype.") && tested.ends_with(&format!(".{}", reference)))
+}
+
+/// `true` for TypeScript declarations that are erased during compilation
+fn is_type_only(decl: &Decl) -> bool {
+    match decl {
+        Decl::Class(d) => d.declare,
+        Decl::Fn(d) => d.declare,
+        Decl::Var(d) => d.declare,
+        Decl::TsInterface(_) | Decl::TsTypeAlias(_) => true,
+        Decl::TsEnum(d) => d.declare,
+        Decl::TsModule(d) => d.declare,
+        Decl::Using(_) => false,
+    }
+}
+
+/// `true` for `Symbol()`, well-known symbols like `Symbol.iterator` and bindings of them
+fn is_symbol(expr: &Expr, aliases: &Aliases) -> bool {
+    match expr {
+        Expr::Member(m) => aliases.global_name(&m.obj).is_some_and(|o| o == "Symbol"),
+        _ => aliases.instance_of(expr).is_some_and(|c| c == "Symbol"),
+    }
+}
+
+/// `true` for symbols and identifiers named like one, such as `sym` or `mySymbol`
+fn is_symbol_like(expr: &Expr, aliases: &Aliases) -> bool {
+    match expr {
+        Expr::Ident(i) if !is_symbol(expr, aliases) && aliases.global_name(expr).is_none() => {
+            let name = i.sym.to_ascii_lowercase();
+            name == "sym" || name.ends_with("symbol")
+        }
+        _ => is_symbol(expr, aliases),
+    }
+}
+
+/// `false` if an expression was created with a literal or constructor other than
+/// `constructors`, where `Array` stands for typed arrays too. Unknown receivers may be anything.
+fn may_be(expr: &Expr, aliases: &Aliases, constructors: &[&str]) -> bool {
+    aliases.instance_of(expr).is_none_or(|c| {
+        constructors.contains(&c.as_str())
+            || constructors.contains(&"Array") && TYPED_ARRAYS.contains(&c.as_str())
+    })
+}
+
+/// `true` for number literals like `1` or `-1`
+fn is_number(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(Lit::Num(_)) => true,
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) => matches!(arg.deref(), Expr::Lit(Lit::Num(_))),
+        _ => false,
+    }
+}
+
+/// Whether an expression is a `WeakMap`, `WeakSet` or `FinalizationRegistry` created with
+/// its constructor, directly or through a binding
+fn is_weak_collection(expr: &Expr, aliases: &Aliases) -> bool {
+    aliases
+        .instance_of(expr)
+        .is_some_and(|c| matches!(c.as_str(), "WeakMap" | "WeakSet" | "FinalizationRegistry"))
+}
+
+/// `true` if an object literal has a property with the given name
+fn has_key(object: &ObjectLit, name: &str) -> bool {
+    object.props.iter().any(|p| match p {
+        PropOrSpread::Prop(p) => match p.deref() {
+            Prop::Shorthand(i) => i.sym == name,
+            Prop::KeyValue(KeyValueProp { key, .. }) => match key {
+                PropName::Ident(i) => i.sym == name,
+                PropName::Str(s) => s.value == name,
+                _ => false,
+            },
+            _ => false,
+        },
+        PropOrSpread::Spread(_) => false,
+    })
+}
+
+/// Whether an expression is an `ArrayBuffer`, guessed by its name unless it was created with a
+/// known constructor
+fn is_array_buffer(expr: &Expr, aliases: &Aliases) -> bool {
+    if let Some(constructor) = aliases.instance_of(expr) {
+        return matches!(constructor.as_str(), "ArrayBuffer" | "SharedArrayBuffer");
+    }
+    let name = match expr {
+        Expr::Ident(i) => &i.sym,
+        Expr::Member(MemberExpr {
+            prop: MemberProp::Ident(i),
+            ..
+        }) => &i.sym,
+        _ => return false,
+    };
+    name.to_ascii_lowercase().ends_with("buffer")
+}
+
+/// `true` for calls that return an iterator rather than an array, like `map.values()`
+fn is_iterator(expr: &Expr, aliases: &Aliases) -> bool {
+    let Expr::Call(CallExpr {
+        callee: Callee::Expr(callee),
+        ..
+    }) = expr
+    else {
+        return false;
+    };
+    let Expr::Member(m) = callee.deref() else {
+        return false;
+    };
+    let MemberProp::Ident(method) = &m.prop else {
+        return false;
+    };
+    match aliases.global_name(&m.obj).as_deref() {
+        // Object.keys() and friends return arrays
+        Some("Object") => false,
+        Some("Iterator") => &method.sym == "from",
+        _ => matches!(
+            method.sym.deref(),
+            "keys" | "values" | "entries" | "matchAll"
+        ),
+    }
+}
+
+/// `true` if the block compiles source at runtime with `new Function` or `eval`
+fn evaluates_code(block: &BlockStmt, aliases: &Aliases) -> bool {
+    struct Finder<'a> {
+        aliases: &'a Aliases,
+        found: bool,
+    }
+    impl Visit for Finder<'_> {
+        fn visit_call_expr(&mut self, n: &CallExpr) {
+            if let Callee::Expr(callee) = &n.callee {
+                self.found |= self
+                    .aliases
+                    .global_name(callee)
+                    .is_some_and(|c| c == "Function" || c == "eval");
+            }
+            n.visit_children_with(self)
+        }
+
+        fn visit_new_expr(&mut self, n: &NewExpr) {
+            self.found |= self
+                .aliases
+                .global_name(&n.callee)
+                .is_some_and(|c| c == "Function");
+            n.visit_children_with(self)
+        }
+    }
+
+    let mut finder = Finder {
+        aliases,
+        found: false,
+    };
+    block.visit_with(&mut finder);
+    finder.found
 }